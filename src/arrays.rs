@@ -0,0 +1,111 @@
+use ratel::ast as Ast;
+use dynamic_typing::{ Type, SafeBorrow, CustomType, ScopeRef };
+use statics::ARRAY_PROTOTYPE;
+use expressions::determine_expression_type;
+use functions::callback_type_from;
+
+pub fn array_of(element_type: Type) -> Type {
+    Type::Composed { outer: (*ARRAY_PROTOTYPE).clone(), inner: Box::new(element_type) }
+}
+
+pub fn element_type(array_type: &Type) -> Option<Type> {
+    match array_type {
+        Type::Composed { outer, inner } => {
+            if !outer.borrow_safe(|outer| outer.is_array()) {
+                return None;
+            }
+
+            match **inner {
+                // nothing is known about the elements of an empty array literal
+                Type::Mixed(ref types) if types.is_empty() => Some(Type::Undefined),
+                ref inner => Some(inner.clone()),
+            }
+        },
+
+        _ => None,
+    }
+}
+
+// the element type and method name of calls like `list.map(...)`, when the receiver is an array
+pub fn array_method(callee: &Ast::Expression, scope: &ScopeRef) -> Option<(Type, String)> {
+    let member_expression = match callee {
+        Ast::Expression::Member(member_expression) => member_expression,
+        _ => return None,
+    };
+
+    let object_type = determine_expression_type(&member_expression.object.item, scope).ok()?;
+
+    element_type(&object_type).map(|element_type| (element_type, member_expression.property.item.to_string()))
+}
+
+// the values an array method passes into the callback it receives as its first argument
+pub fn callback_argument_types(callee: &Ast::Expression, index: usize, scope: &ScopeRef) -> Option<Vec<Type>> {
+    if index != 0 {
+        return None;
+    }
+
+    let (element_type, method) = array_method(callee, scope)?;
+
+    match method.as_str() {
+        "every" | "filter" | "find" | "findIndex" | "flatMap" | "forEach" | "map" | "some" => {
+            Some(vec!(element_type.clone(), Type::Number, array_of(element_type)))
+        },
+
+        "reduce" | "reduceRight" => Some(vec!(Type::Undefined, element_type.clone(), Type::Number, array_of(element_type))),
+        "sort" => Some(vec!(element_type.clone(), element_type)),
+        _ => None,
+    }
+}
+
+// array methods whose result depends on the element type, every other method keeps its declared return type
+pub fn array_call_type(call_expression: &Ast::expression::CallExpression, scope: &ScopeRef) -> Option<Type> {
+    let (element_type, method) = array_method(&call_expression.callee.item, scope)?;
+
+    match method.as_str() {
+        "fill" | "filter" | "reverse" | "slice" | "sort" | "splice" => Some(array_of(element_type)),
+        "find" | "pop" | "shift" => Some(Type::union(vec!(element_type, Type::Undefined))),
+
+        "concat" => {
+            let mut types = vec!(element_type);
+
+            for argument in call_expression.arguments.iter() {
+                let argument_type = determine_expression_type(argument, scope).ok()?;
+
+                types.push(self::element_type(&argument_type).unwrap_or(argument_type));
+            }
+
+            Some(array_of(Type::union(types)))
+        },
+
+        "map" => callback_return_type(call_expression, scope).map(array_of),
+
+        "flatMap" => callback_return_type(call_expression, scope).map(|return_type| {
+            let inner_type = self::element_type(&return_type).unwrap_or(return_type);
+
+            array_of(inner_type)
+        }),
+
+        "reduce" | "reduceRight" => callback_return_type(call_expression, scope),
+        _ => None,
+    }
+}
+
+// arguments of push and unshift end up as elements of the array
+pub fn element_arguments(callee: &Ast::Expression, scope: &ScopeRef) -> Option<Type> {
+    let (element_type, method) = array_method(callee, scope)?;
+
+    match method.as_str() {
+        "push" | "unshift" => Some(element_type),
+        _ => None,
+    }
+}
+
+fn callback_return_type(call_expression: &Ast::expression::CallExpression, scope: &ScopeRef) -> Option<Type> {
+    let callback = call_expression.arguments.iter().next()?;
+    let argument_types = callback_argument_types(&call_expression.callee.item, 0, scope)?;
+
+    match callback_type_from(callback, callback.start, &argument_types, scope).ok()? {
+        Type::Function(function_type) => Some(function_type.borrow_safe(|function_type| function_type.return_type(&argument_types))),
+        _ => None,
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+use std::sync::Arc;
+use failure::Error;
+use serde_json;
+
+use error::{ Diagnostics, ValidationError };
+use modules::ModuleRecord;
+
+// a known diagnostic, the fingerprint of the source line keeps it matching when code above moves
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct BaselineEntry {
+    file: String,
+    rule: String,
+    fingerprint: String,
+    count: usize,
+}
+
+// the diagnostics a project had when it adopted the checker, only new ones are reported
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct Baseline {
+    entries: Vec<BaselineEntry>,
+}
+
+type BaselineKey = (String, String, String);
+
+impl Baseline {
+    pub fn read(path: &Path) -> Result<Baseline, Error> {
+        let mut source = String::new();
+
+        File::open(path)?.read_to_string(&mut source)?;
+
+        Ok(serde_json::from_str(&source)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), Error> {
+        let output = serde_json::to_string_pretty(self)?;
+
+        File::create(path)?.write_all(output.as_bytes())?;
+
+        Ok(())
+    }
+
+    // files are recorded relative to the project directory, so the baseline can be checked in
+    pub fn from_modules(modules: &[Arc<ModuleRecord>], directory: &Path) -> Baseline {
+        let mut counts: HashMap<BaselineKey, usize> = HashMap::new();
+
+        for module in modules {
+            for error in module.errors.keys() {
+                *counts.entry(baseline_key(module, error, directory)).or_insert(0) += 1;
+            }
+        }
+
+        let mut entries: Vec<BaselineEntry> = counts.into_iter()
+            .map(|((file, rule, fingerprint), count)| BaselineEntry { file, rule, fingerprint, count })
+            .collect();
+
+        // a stable order keeps diffs of the baseline small
+        entries.sort_by(|a, b| (&a.file, &a.rule, &a.fingerprint).cmp(&(&b.file, &b.rule, &b.fingerprint)));
+
+        Baseline { entries }
+    }
+
+    pub fn diagnostic_count(&self) -> usize {
+        self.entries.iter().map(|entry| entry.count).sum()
+    }
+
+    // the diagnostics of the module that are not in the baseline. An entry matches as many
+    // diagnostics as it counted, additional ones on an identical line are new
+    pub fn new_diagnostics(&self, module: &ModuleRecord, directory: &Path) -> Diagnostics {
+        let mut remaining: HashMap<BaselineKey, usize> = self.entries.iter()
+            .map(|entry| ((entry.file.clone(), entry.rule.clone(), entry.fingerprint.clone()), entry.count))
+            .collect();
+
+        let mut errors: Vec<(&Arc<ValidationError>, _)> = module.errors.iter().collect();
+
+        errors.sort_by_key(|(error, _)| error.location().start);
+
+        errors.into_iter()
+            .filter(|(error, _)| {
+                match remaining.get_mut(&baseline_key(module, error, directory)) {
+                    Some(count) if *count > 0 => { *count -= 1; false },
+                    _ => true,
+                }
+            })
+            .map(|(error, severity)| (error.clone(), *severity))
+            .collect()
+    }
+}
+
+fn baseline_key(module: &ModuleRecord, error: &ValidationError, directory: &Path) -> BaselineKey {
+    let file = module.path.strip_prefix(directory).unwrap_or(&module.path).to_string_lossy().replace('\\', "/");
+
+    (file, error.code().to_owned(), fingerprint(source_line(&module.source, error.location().start)))
+}
+
+fn source_line(source: &str, offset: u32) -> &str {
+    let offset = (offset as usize).min(source.len());
+    let start = source[..offset].rfind('\n').map(|index| index + 1).unwrap_or(0);
+    let end = source[offset..].find('\n').map(|index| offset + index).unwrap_or(source.len());
+
+    &source[start..end]
+}
+
+// FNV-1a of the line with its whitespace collapsed, indentation changes do not matter and the
+// hash is the same for every build of the checker
+fn fingerprint(line: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+
+    for word in line.split_whitespace() {
+        for byte in word.bytes().chain(Some(b' ')) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    format!("{:016x}", hash)
+}
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use ratel::ast as Ast;
+use failure::*;
+use dynamic_typing::{
+    Type, CustomTypeObject, FunctionType, FunctionKind, ObjectType, CustomType, ScopeRef,
+    SafeBorrow, new_mutex_ref
+};
+use statics::OBJECT_PROTOTYPE;
+use expressions::determine_expression_type;
+use functions::{ function_type_from, annotated_arguments, function_scope, return_type_of };
+use objects::property_to_string;
+use error::TypeError;
+
+pub fn type_from_class<'ast>(class: &Ast::Class<'ast, Ast::OptionalName<'ast>>, scope: &ScopeRef) -> Result<Type, Error> {
+    let mut constructor = FunctionType::new(vec!());
+    let mut static_properties = HashMap::new();
+    let mut instance_fields = HashMap::new();
+    let mut methods = vec!();
+
+    let Ast::OptionalName(name) = class.name;
+
+    let name = match name {
+        Some(name) => Some(name.to_string()),
+        None => None,
+    };
+
+    let parent_prototype = match class.extends {
+        Some(expression) => Some(determine_expression_type(&expression.item, scope)?),
+        None => None,
+    };
+
+    // instances of the class inherit from the prototype of the parent class, while the class
+    // itself inherits the static members of the parent class
+    let (parent_prototype, parent_class): (Option<CustomTypeObject>, Option<CustomTypeObject>) = match parent_prototype {
+        Some(proto) => match proto {
+            Type::Object(ref object_type) => (Some(CustomTypeObject::from(object_type)), None),
+            Type::Function(ref func_type) => {
+                let prototype = func_type.borrow_safe(|func_type| func_type.properties.get("prototype").cloned());
+
+                let prototype = match prototype {
+                    Some(Type::Object(ref object_type)) => CustomTypeObject::from(object_type),
+                    Some(Type::Function(ref prototype_func)) => CustomTypeObject::from(prototype_func),
+                    _ => return Err(TypeError::IncompatiblePrototype { prototype: proto.to_string() }.into()),
+                };
+
+                (Some(prototype), Some(CustomTypeObject::from(func_type)))
+            },
+            _ => return Err(TypeError::IncompatiblePrototype { prototype: proto.to_string() }.into())
+        },
+
+        None => (None, None),
+    };
+
+    let prototype_name = match name {
+        Some(ref name) => Some(format!("{}Prototype", name)),
+        None => None
+    };
+
+    let prototype = new_mutex_ref(ObjectType::new(prototype_name, HashMap::new(), parent_prototype));
+
+    // methods and the constructor see an instance of the class as their this
+    let instance_type = Type::from(ObjectType::new(name.clone(), HashMap::new(), Some(CustomTypeObject::from(&prototype))));
+
+    for member in class.body.body.iter() {
+        match member.item {
+            Ast::ClassMember::Method { is_static, key, kind, value } => {
+                let Ast::Function { params, body, .. } = value.item;
+
+                match kind {
+                    Ast::MethodKind::Constructor => {
+                        let arguments = annotated_arguments(&params, &[], member.start, scope);
+                        let constructor_scope = function_scope(&arguments, Some(instance_type.clone()), scope);
+
+                        collect_instance_fields(&body, &constructor_scope, &mut instance_fields);
+
+                        constructor = FunctionType::new(arguments);
+                    },
+
+                    _ => methods.push((is_static, property_to_string(&key.item), kind, member.start, params, body)),
+                }
+            },
+
+            Ast::ClassMember::Literal { is_static, key, value } => {
+                let property_name = property_to_string(&key.item);
+                let value_type = determine_expression_type(&value, scope)?;
+
+                if is_static {
+                    static_properties.insert(property_name, value_type);
+                } else {
+                    instance_fields.insert(property_name, value_type);
+                }
+            },
+
+            Ast::ClassMember::Error => (),
+        }
+    }
+
+    if let Some(ref name) = name {
+        constructor.assign_name(name.to_string());
+    }
+
+    constructor.set_kind(FunctionKind::Class);
+    constructor.properties.extend(static_properties);
+
+    if let Some(parent_class) = parent_class {
+        constructor.set_prototype(parent_class);
+    }
+
+    let constructor = new_mutex_ref(constructor);
+    let constructor_type = Type::from(&constructor);
+
+    prototype.borrow_mut_safe(|prototype| {
+        prototype.properties.extend(instance_fields);
+        prototype.properties.insert(String::from("constructor"), constructor_type.clone());
+    });
+
+    constructor.borrow_mut_safe(|constructor| {
+        constructor.properties.insert("prototype".to_owned(), Type::from(&prototype));
+    });
+
+    // members are typed last, so their bodies can already access fields through this
+    for (is_static, property_name, kind, start, params, body) in methods {
+        let this_type = if is_static { constructor_type.clone() } else { instance_type.clone() };
+        let arguments = annotated_arguments(&params, &[], start, scope);
+        let member_scope = function_scope(&arguments, Some(this_type.clone()), scope);
+
+        let member_type = match kind {
+            Ast::MethodKind::Get => return_type_of(&body, &member_scope),
+
+            Ast::MethodKind::Set => arguments.first()
+                .map(|argument| argument.current_type().clone())
+                .unwrap_or(Type::Undefined),
+
+            _ => {
+                let mut method = function_type_from(Some(property_name.clone()), &params, &body, Some(this_type), start, scope)?;
+
+                method.set_kind(FunctionKind::Method);
+
+                Type::from(method)
+            },
+        };
+
+        if is_static {
+            constructor.borrow_mut_safe(|constructor| insert_member(&mut constructor.properties, property_name, member_type, kind));
+        } else {
+            prototype.borrow_mut_safe(|prototype| insert_member(&mut prototype.properties, property_name, member_type, kind));
+        }
+    }
+
+    Ok(constructor_type)
+}
+
+fn insert_member(properties: &mut HashMap<String, Type>, name: String, member_type: Type, kind: Ast::MethodKind) {
+    match kind {
+        // a getter is the better source for the property type, so setters never replace it
+        Ast::MethodKind::Set => {
+            properties.entry(name).or_insert(member_type);
+        },
+
+        _ => {
+            properties.insert(name, member_type);
+        },
+    }
+}
+
+pub fn instance_of(constructor_type: &Type) -> Result<Type, Error> {
+    let constructor = match constructor_type {
+        Type::Function(constructor) => constructor,
+        _ => return Err(TypeError::NotConstructor { type_name: constructor_type.to_string() }.into()),
+    };
+
+    let (is_constructor, name, prototype) = constructor.borrow_safe(|constructor| {
+        (constructor.is_constructor(), constructor.name().to_owned(), constructor.properties.get("prototype").cloned())
+    });
+
+    if !is_constructor {
+        return Err(TypeError::NotConstructor { type_name: constructor_type.to_string() }.into());
+    }
+
+    let prototype = match prototype {
+        Some(Type::Object(ref object_type)) => CustomTypeObject::from(object_type),
+        Some(Type::Function(ref func_type)) => CustomTypeObject::from(func_type),
+        _ => CustomTypeObject::from(&*OBJECT_PROTOTYPE),
+    };
+
+    Ok(Type::from(ObjectType::new(Some(name), HashMap::new(), Some(prototype))))
+}
+
+pub fn class_expression_from<'ast>(class: Ast::Class<'ast, Ast::MandatoryName<'ast>>) -> Ast::Class<'ast, Ast::OptionalName<'ast>> {
+    let Ast::MandatoryName(name) = class.name;
+
+    Ast::Class { name: Ast::OptionalName(Some(name)), extends: class.extends, body: class.body }
+}
+
+fn collect_instance_fields(body: &Ast::Block<Ast::Statement>, scope: &ScopeRef, fields: &mut HashMap<String, Type>) {
+    for statement in body.body.iter() {
+        let expression = match statement.item {
+            Ast::Statement::Expression(expression) => expression,
+            _ => continue,
+        };
+
+        let binary_expression = match expression.item {
+            Ast::Expression::Binary(binary_expression) => binary_expression,
+            _ => continue,
+        };
+
+        match binary_expression.operator {
+            Ast::OperatorKind::Assign => (),
+            _ => continue,
+        };
+
+        if let Ast::Expression::Member(member_expression) = binary_expression.left.item {
+            if let Ast::Expression::This(_) = member_expression.object.item {
+                // errors inside of the constructor body are reported by the validation pass
+                let field_type = determine_expression_type(&binary_expression.right, scope).unwrap_or(Type::Undefined);
+
+                fields.entry(member_expression.property.item.to_string()).or_insert(field_type);
+            }
+        }
+    }
+}
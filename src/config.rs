@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::fs::{ self, File };
+use std::io::prelude::*;
+use std::path::{ Path, PathBuf };
+use failure::Error;
+use toml;
+
+use dynamic_typing::{ Variable, WideningPolicy };
+use error::{ ErrorVec, Diagnostics, ValidationError };
+use statics::{ standard_globals, browser_globals, node_globals };
+
+pub const CONFIG_FILE: &str = "rusty.toml";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Off,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    Es2020,
+    Browser,
+    Node,
+}
+
+// the project configuration, globs and paths are relative to the directory of the config file
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Config {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+
+    // severities by rule code like "R001" or diagnostic name, rules that are not listed keep
+    // their default severity
+    pub diagnostics: HashMap<String, Severity>,
+    pub environments: Vec<Environment>,
+
+    // directories bare specifiers are looked up in when no node_modules directory has them
+    pub resolution_roots: Vec<String>,
+
+    // whether variables declared without a value take the type of their first assignment
+    pub widening: WideningPolicy,
+
+    #[serde(skip)]
+    pub directory: PathBuf,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            include: vec!(String::from("**")),
+            exclude: vec!(String::from("**/node_modules/**")),
+            diagnostics: HashMap::new(),
+            environments: vec!(Environment::Es2020),
+            resolution_roots: vec!(),
+            widening: WideningPolicy::default(),
+            directory: PathBuf::new(),
+        }
+    }
+}
+
+impl Config {
+    // the closest config file in the directory of the analyzed file or above it
+    pub fn discover(analyzed_file: &Path) -> Result<Config, Error> {
+        let analyzed_file = fs::canonicalize(analyzed_file)?;
+        let start = analyzed_file.parent().unwrap_or_else(|| Path::new("/"));
+
+        for directory in start.ancestors() {
+            let path = directory.join(CONFIG_FILE);
+
+            if !path.is_file() {
+                continue;
+            }
+
+            let mut source = String::new();
+
+            File::open(&path)?.read_to_string(&mut source)?;
+
+            let mut config: Config = toml::from_str(&source)?;
+
+            config.directory = directory.to_owned();
+
+            return Ok(config);
+        }
+
+        Ok(Config { directory: start.to_owned(), ..Config::default() })
+    }
+
+    pub fn severity(&self, error: &ValidationError) -> Severity {
+        self.diagnostics.get(error.code())
+            .or_else(|| self.diagnostics.get(error.name()))
+            .cloned()
+            .unwrap_or_else(|| default_severity(error))
+    }
+
+    // the diagnostics of errors that were found outside of a validation context
+    pub fn classify(&self, errors: ErrorVec) -> Diagnostics {
+        errors.into_iter()
+            .map(|error| { let severity = self.severity(&error); (error, severity) })
+            .filter(|(_, severity)| *severity != Severity::Off)
+            .collect()
+    }
+
+    // whether diagnostics of the file are reported, files outside of the project never are
+    pub fn includes(&self, path: &Path) -> bool {
+        let relative = match path.strip_prefix(&self.directory) {
+            Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+            Err(_) => return false,
+        };
+
+        self.include.iter().any(|pattern| glob_matches(pattern, &relative))
+            && !self.exclude.iter().any(|pattern| glob_matches(pattern, &relative))
+    }
+
+    pub fn resolution_roots(&self) -> Vec<PathBuf> {
+        self.resolution_roots.iter().map(|root| self.directory.join(root)).collect()
+    }
+
+    // the bindings of the static root scope
+    pub fn globals(&self) -> Vec<Variable> {
+        let mut globals = vec!();
+
+        for environment in &self.environments {
+            globals.extend(match environment {
+                Environment::Es2020 => standard_globals(),
+                Environment::Browser => browser_globals(),
+                Environment::Node => node_globals(),
+            });
+        }
+
+        globals
+    }
+}
+
+// diagnostics about code that is valid but may not do what was intended are not errors
+fn default_severity(error: &ValidationError) -> Severity {
+    match error {
+        ValidationError::CyclicImport { .. } => Severity::Warning,
+        ValidationError::LooseEquality { .. } => Severity::Info,
+        _ => Severity::Error,
+    }
+}
+
+// `**` matches any number of directories, `*` and `?` match within a single file or directory name
+fn glob_matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').filter(|segment| !segment.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+
+    segments_match(&pattern, &path)
+}
+
+fn segments_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+
+        Some((&"**", rest)) => (0..=path.len()).any(|skipped| segments_match(rest, &path[skipped..])),
+
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path_rest)) => name_matches(segment.as_bytes(), name.as_bytes()) && segments_match(rest, path_rest),
+            None => false,
+        },
+    }
+}
+
+fn name_matches(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skipped| name_matches(rest, &name[skipped..])),
+        Some((b'?', rest)) => !name.is_empty() && name_matches(rest, &name[1..]),
+        Some((character, rest)) => name.first() == Some(character) && name_matches(rest, &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn double_star_matches_any_number_of_directories() {
+        assert!(glob_matches("**", "index.js"));
+        assert!(glob_matches("src/**/*.js", "src/index.js"));
+        assert!(glob_matches("src/**/*.js", "src/modules/graph/index.js"));
+        assert!(glob_matches("**/node_modules/**", "node_modules/lodash/index.js"));
+        assert!(glob_matches("**/node_modules/**", "packages/app/node_modules/lodash/index.js"));
+
+        assert!(!glob_matches("src/**/*.js", "test/index.js"));
+        assert!(!glob_matches("src/**/*.js", "src/modules/index.ts"));
+    }
+
+    #[test]
+    fn single_star_stays_within_a_name() {
+        assert!(glob_matches("src/*.js", "src/index.js"));
+        assert!(glob_matches("src/?.js", "src/a.js"));
+
+        assert!(!glob_matches("src/*.js", "src/modules/index.js"));
+        assert!(!glob_matches("src/?.js", "src/ab.js"));
+    }
+
+    #[test]
+    fn reads_kebab_case_keys() {
+        let config: Config = toml::from_str("resolution-roots = [\"vendor\"]\nwidening = \"never\"\n").unwrap();
+
+        assert_eq!(config.resolution_roots, vec!(String::from("vendor")));
+        assert_eq!(config.widening, WideningPolicy::Never);
+    }
+}
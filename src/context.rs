@@ -1,20 +1,32 @@
+use std::sync::Arc;
 use dynamic_typing::{ Scope, MutexRef, SafeBorrow  };
 use expression_meta_data::MetaHashMap;
-use error::ErrorVec;
+use error::{ Diagnostics, ValidationError };
+use config::{ Config, Severity };
 use expression_meta_data::MetaCarry;
 use ast_nodes::ExpressionNodeStruct;
 
 pub struct Context<'own> {
     pub data_map: MutexRef<MetaHashMap<'own>>,
     pub scope: MutexRef<Scope>,
-    pub errors: ErrorVec
+    pub errors: Diagnostics,
+    pub config: Arc<Config>,
 }
 
 impl<'own> Context<'own> {
-    pub fn new(scope: MutexRef<Scope>, data_map: MutexRef<MetaHashMap<'own>>) -> Self {
-        let errors = ErrorVec::new();
+    pub fn new(scope: MutexRef<Scope>, data_map: MutexRef<MetaHashMap<'own>>, config: Arc<Config>) -> Self {
+        let errors = Diagnostics::new();
 
-        Self { scope, data_map, errors }
+        Self { scope, data_map, errors, config }
+    }
+
+    // records the error with the severity configured for its rule, disabled rules are dropped
+    pub fn report(&mut self, error: Arc<ValidationError>) {
+        let severity = self.config.severity(&error);
+
+        if severity != Severity::Off {
+            self.errors.insert(error, severity);
+        }
     }
 
 
@@ -41,11 +53,11 @@ impl<'own> Context<'own> {
         Context {
             data_map: self.data_map.clone(),
             scope: scope.clone(),
-            errors: ErrorVec::new()
+            errors: Diagnostics::new(),
+            config: self.config.clone(),
         }
     }
 
-    #[allow(dead_code)]
     pub fn join(&mut self, other: Context) {
         self.errors.extend(other.errors);
     }
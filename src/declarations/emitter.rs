@@ -0,0 +1,396 @@
+use std::collections::{ HashMap, HashSet };
+use uuid::Uuid;
+
+use dynamic_typing::{
+    Type, ObjectType, FunctionType, FunctionKind, VariableKind, CustomType, CustomTypeObject, MutexRef,
+    Location, Scoped, ScopeRef, SafeBorrow
+};
+use statics::OBJECT_PROTOTYPE;
+
+// nested object literals deeper than this are not spelled out
+const MAX_DEPTH: usize = 4;
+
+// renders the top level bindings of a module scope as a declaration file, object literals
+// become interfaces named after the binding that holds them. Used for scripts, which have no
+// export statements
+pub fn emit_declarations(scope: &ScopeRef) -> String {
+    let bindings = scope.variables().iter().map(|variable| {
+        variable.borrow_safe(|variable| {
+            (variable.name().to_owned(), variable.current_type().clone(), variable.kind().clone())
+        })
+    }).collect();
+
+    emit_bindings(bindings, None)
+}
+
+// renders the exports of an ES module, the default export is declared under a local name and
+// exported from there
+pub fn emit_module_declarations(exports: &HashMap<String, Type>) -> String {
+    let mut default_export = None;
+    let mut bindings = vec!();
+
+    for (name, export_type) in sorted(exports.clone()) {
+        if name == "default" {
+            default_export = Some(export_type);
+            continue;
+        }
+
+        bindings.push((name, export_type, VariableKind::Const));
+    }
+
+    match default_export {
+        Some(default_type) => emit_bindings(bindings, Some(("_default", default_type, "export default"))),
+        None if bindings.is_empty() => String::from("export {};\n"),
+        None => emit_bindings(bindings, None),
+    }
+}
+
+// renders the value of `module.exports` of a CommonJS module as an `export =` assignment
+pub fn emit_commonjs_declarations(exports_object: &Type) -> String {
+    emit_bindings(vec!(), Some(("_exports", exports_object.clone(), "export =")))
+}
+
+// the local export is declared without an export keyword and exported through the given statement
+fn emit_bindings(bindings: Vec<(String, Type, VariableKind)>, local_export: Option<(&str, Type, &str)>) -> String {
+    let mut emitter = Emitter { interfaces: vec!(), interface_names: HashMap::new(), used_names: HashSet::new(), in_progress: HashSet::new() };
+
+    for &(ref name, _, _) in &bindings {
+        emitter.used_names.insert(name.clone());
+    }
+
+    let mut statements = vec!();
+
+    for (name, binding_type, kind) in bindings {
+        statements.push(emitter.binding(&name, &binding_type, &kind));
+    }
+
+    if let Some((local_name, local_type, export_statement)) = local_export {
+        let mut name = local_name.to_owned();
+
+        while emitter.used_names.contains(&name) {
+            name = format!("_{}", name);
+        }
+
+        emitter.used_names.insert(name.clone());
+
+        let statement = emitter.binding(&name, &local_type, &VariableKind::Const);
+
+        statements.push(format!("{}\n{} {};", statement.trim_start_matches("export "), export_statement, name));
+    }
+
+    let mut output: Vec<String> = emitter.interfaces;
+
+    output.extend(statements);
+    output.join("\n\n") + "\n"
+}
+
+struct Emitter {
+    interfaces: Vec<String>,
+    interface_names: HashMap<Uuid, String>,
+    used_names: HashSet<String>,
+    // objects whose interface is currently being written, references to them are by name
+    in_progress: HashSet<Uuid>,
+}
+
+impl Emitter {
+    fn binding(&mut self, name: &str, binding_type: &Type, kind: &VariableKind) -> String {
+        let declaration = if *kind == VariableKind::Const { "const" } else { "let" };
+
+        match binding_type {
+            Type::Function(function) => {
+                let (function_kind, function_name) = function.borrow_safe(|function| {
+                    (function.kind().clone(), function.name().to_owned())
+                });
+
+                match function_kind {
+                    FunctionKind::Class => self.class(name, function),
+                    FunctionKind::Arrow => format!("export declare {} {}: {};", declaration, name, self.type_reference(binding_type, 0)),
+
+                    // functions assigned to a differently named binding are values, not declarations
+                    _ if function_name != name => format!("export declare {} {}: {};", declaration, name, self.type_reference(binding_type, 0)),
+                    _ => format!("export declare function {}{};", name, self.signature(function, ": ", 0)),
+                }
+            },
+
+            Type::Object(object) if self.is_literal(object) => {
+                let interface_name = self.interface(object, name);
+
+                format!("export declare {} {}: {};", declaration, name, interface_name)
+            },
+
+            _ => format!("export declare {} {}: {};", declaration, name, self.type_reference(binding_type, 0)),
+        }
+    }
+
+    fn class(&mut self, name: &str, constructor: &MutexRef<FunctionType>) -> String {
+        let (static_members, prototype) = constructor.borrow_safe(|constructor| {
+            let mut statics = constructor.properties.clone();
+            let prototype = statics.remove("prototype");
+
+            (statics, prototype)
+        });
+
+        let mut lines = vec!(format!("export declare class {} {{", name));
+
+        lines.push(format!("    constructor{};", self.parameters(constructor, 0)));
+
+        for (member, member_type) in sorted(static_members) {
+            lines.push(format!("    static {}", self.member(&member, &member_type, 0)));
+        }
+
+        if let Some(Type::Object(prototype)) = prototype {
+            let members = prototype.borrow_safe(|prototype| prototype.properties.clone());
+
+            for (member, member_type) in sorted(members) {
+                if member == "constructor" {
+                    continue;
+                }
+
+                lines.push(format!("    {}", self.member(&member, &member_type, 0)));
+            }
+        }
+
+        lines.push(String::from("}"));
+        lines.join("\n")
+    }
+
+    // writes an interface for the object and returns its name, every object gets one interface
+    fn interface(&mut self, object: &MutexRef<ObjectType>, suggested_name: &str) -> String {
+        let id = object.borrow_safe(|object| *object.id());
+
+        if let Some(name) = self.interface_names.get(&id) {
+            return name.clone();
+        }
+
+        let name = self.unique_name(suggested_name);
+        let properties = object.borrow_safe(|object| object.properties.clone());
+
+        self.interface_names.insert(id, name.clone());
+        self.in_progress.insert(id);
+
+        let mut lines = vec!(format!("export interface {} {{", name));
+
+        for (member, member_type) in sorted(properties) {
+            lines.push(format!("    {}", self.member(&member, &member_type, 0)));
+        }
+
+        lines.push(String::from("}"));
+
+        self.in_progress.remove(&id);
+        self.interfaces.push(lines.join("\n"));
+
+        name
+    }
+
+    fn member(&mut self, name: &str, member_type: &Type, depth: usize) -> String {
+        if let Type::Function(function) = member_type {
+            if function.borrow_safe(|function| *function.kind() == FunctionKind::Method) {
+                return format!("{}{};", property_name(name), self.signature(function, ": ", depth));
+            }
+        }
+
+        format!("{}: {};", property_name(name), self.type_reference(member_type, depth + 1))
+    }
+
+    fn signature(&mut self, function: &MutexRef<FunctionType>, separator: &str, depth: usize) -> String {
+        let return_type = function.borrow_safe(|function| function.return_type(&vec!()));
+        let parameters = self.parameters(function, depth);
+
+        // functions without a return statement return undefined, which is the unknown type here
+        let return_type = match return_type {
+            Type::Undefined => String::from("void"),
+            ref return_type => self.type_reference(return_type, depth + 1),
+        };
+
+        format!("{}{}{}", parameters, separator, return_type)
+    }
+
+    fn parameters(&mut self, function: &MutexRef<FunctionType>, depth: usize) -> String {
+        let arguments: Vec<(String, Type)> = function.borrow_safe(|function| {
+            function.arguments().iter()
+                .map(|argument| (argument.name().to_owned(), argument.current_type().clone()))
+                .collect()
+        });
+
+        let parameters: Vec<String> = arguments.iter()
+            .map(|(name, argument_type)| format!("{}: {}", name, self.type_reference(argument_type, depth + 1)))
+            .collect();
+
+        format!("({})", parameters.join(", "))
+    }
+
+    fn type_reference(&mut self, value: &Type, depth: usize) -> String {
+        match value {
+            Type::Number => String::from("number"),
+            Type::String => String::from("string"),
+            Type::Boolean => String::from("boolean"),
+            Type::RegExp => String::from("RegExp"),
+            Type::Null => String::from("null"),
+            Type::Undefined => String::from("any"),
+
+            Type::Mixed(members) => {
+                if members.is_empty() {
+                    return String::from("any");
+                }
+
+                members.iter()
+                    .map(|member| self.type_reference(member, depth))
+                    .collect::<Vec<String>>()
+                    .join(" | ")
+            },
+
+            Type::Composed { outer, inner } => {
+                let (is_array, container) = outer.borrow_safe(|outer| (outer.is_array(), container_name(outer.name())));
+                let inner = match **inner {
+                    // the elements of an empty array literal are not known
+                    Type::Mixed(ref members) if members.is_empty() => String::from("any"),
+                    ref inner => self.type_reference(inner, depth),
+                };
+
+                if !is_array {
+                    return format!("{}<{}>", container, inner);
+                }
+
+                if inner.contains(' ') {
+                    return format!("({})[]", inner);
+                }
+
+                format!("{}[]", inner)
+            },
+
+            Type::Function(function) => {
+                let is_class = function.borrow_safe(|function| *function.kind() == FunctionKind::Class);
+
+                if is_class {
+                    let name = function.borrow_safe(|function| function.name().to_owned());
+
+                    return format!("typeof {}", name);
+                }
+
+                format!("({})", self.signature(function, " => ", depth))
+            },
+
+            Type::Object(object) => {
+                if !self.is_literal(object) {
+                    return self.instance_name(object);
+                }
+
+                let (id, name, properties) = object.borrow_safe(|object| {
+                    let name = if object.is_anonymous() { None } else { Some(object.name().to_owned()) };
+
+                    (*object.id(), name, object.properties.clone())
+                });
+
+                if let Some(name) = self.interface_names.get(&id) {
+                    return name.clone();
+                }
+
+                // named shapes, like declared interfaces or objects held by a variable, get an interface
+                if let Some(name) = name {
+                    return self.interface(object, &name);
+                }
+
+                if self.in_progress.contains(&id) || depth >= MAX_DEPTH {
+                    return String::from("any");
+                }
+
+                if properties.is_empty() {
+                    return String::from("{}");
+                }
+
+                self.in_progress.insert(id);
+
+                let members: Vec<String> = sorted(properties).iter()
+                    .map(|(member, member_type)| self.member(member, member_type, depth))
+                    .collect();
+
+                self.in_progress.remove(&id);
+
+                format!("{{ {} }}", members.join(" "))
+            },
+        }
+    }
+
+    // object literals inherit from Object.prototype directly, everything else is an instance
+    fn is_literal(&self, object: &MutexRef<ObjectType>) -> bool {
+        let prototype = object.borrow_safe(|object| object.prototype().cloned());
+
+        match prototype {
+            Some(prototype) => is_object_prototype(&prototype),
+            None => true,
+        }
+    }
+
+    // instances are named after their class, which is known through the prototype
+    fn instance_name(&self, object: &MutexRef<ObjectType>) -> String {
+        let prototype = object.borrow_safe(|object| object.prototype().cloned());
+        let constructor = prototype.as_ref()
+            .and_then(|prototype| prototype.query_property("constructor", &Location::latest()));
+
+        let name = match (constructor, prototype) {
+            (Some(Type::Function(ref constructor)), _) => constructor.borrow_safe(|constructor| constructor.name().to_owned()),
+            (_, Some(ref prototype)) => container_name(&prototype.name()),
+            _ => return String::from("object"),
+        };
+
+        // the element types of built-in containers are not tracked on their instances
+        match name.as_str() {
+            "Promise" | "Set" | "Array" => format!("{}<any>", name),
+            "Map" => format!("{}<any, any>", name),
+            _ => name,
+        }
+    }
+
+    fn unique_name(&mut self, suggested_name: &str) -> String {
+        let mut characters = suggested_name.chars();
+        let base = match characters.next() {
+            Some(first) => first.to_uppercase().chain(characters).collect::<String>(),
+            None => String::from("Anonymous"),
+        };
+
+        let mut name = base.clone();
+        let mut counter = 1;
+
+        // interfaces share their namespace with classes, so `const Color = {}` needs another name
+        if self.used_names.contains(&name) {
+            name = format!("{}Type", base);
+        }
+
+        while self.used_names.contains(&name) {
+            counter += 1;
+            name = format!("{}Type{}", base, counter);
+        }
+
+        self.used_names.insert(name.clone());
+
+        name
+    }
+}
+
+fn is_object_prototype(prototype: &CustomTypeObject) -> bool {
+    prototype.id() == OBJECT_PROTOTYPE.borrow_safe(|prototype| *prototype.id())
+}
+
+fn sorted(properties: HashMap<String, Type>) -> Vec<(String, Type)> {
+    let mut properties: Vec<(String, Type)> = properties.into_iter().collect();
+
+    properties.sort_by(|left, right| left.0.cmp(&right.0));
+    properties
+}
+
+// prototypes are named like `ArrayPrototype`, the type they describe is `Array`
+fn container_name(prototype_name: &str) -> String {
+    prototype_name.trim_end_matches("Prototype").to_owned()
+}
+
+fn property_name(name: &str) -> String {
+    let is_identifier = name.chars().enumerate().all(|(index, character)| {
+        character == '_' || character == '$' || character.is_alphabetic() || (index > 0 && character.is_numeric())
+    });
+
+    if is_identifier && !name.is_empty() {
+        return name.to_owned();
+    }
+
+    format!("\"{}\"", name)
+}
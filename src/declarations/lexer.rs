@@ -0,0 +1,158 @@
+use error::DeclarationError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Identifier(String),
+    StringLiteral(String),
+    NumberLiteral(String),
+    Punctuator(char),
+    Arrow,
+    Ellipsis,
+    End,
+}
+
+impl Token {
+    pub fn is_punctuator(&self, punctuator: char) -> bool {
+        *self == Token::Punctuator(punctuator)
+    }
+
+    pub fn is_identifier(&self, identifier: &str) -> bool {
+        match self {
+            Token::Identifier(name) => name == identifier,
+            _ => false,
+        }
+    }
+}
+
+impl ToString for Token {
+    fn to_string(&self) -> String {
+        match self {
+            Token::Identifier(name) => name.clone(),
+            Token::StringLiteral(value) => format!("\"{}\"", value),
+            Token::NumberLiteral(value) => value.clone(),
+            Token::Punctuator(punctuator) => punctuator.to_string(),
+            Token::Arrow => String::from("=>"),
+            Token::Ellipsis => String::from("..."),
+            Token::End => String::from("end of file"),
+        }
+    }
+}
+
+// splits a declaration file into tokens and their offsets, comments are dropped. Operators are
+// single characters, so `>>` closing nested generics does not need special treatment
+pub fn tokenize(source: &str) -> Result<Vec<(Token, u32)>, DeclarationError> {
+    let characters: Vec<(usize, char)> = source.char_indices().collect();
+    let mut tokens = vec!();
+    let mut index = 0;
+
+    while index < characters.len() {
+        let (offset, character) = characters[index];
+        let next = characters.get(index + 1).map(|(_, character)| *character);
+
+        if character.is_whitespace() {
+            index += 1;
+            continue;
+        }
+
+        if character == '/' && next == Some('/') {
+            while index < characters.len() && characters[index].1 != '\n' {
+                index += 1;
+            }
+
+            continue;
+        }
+
+        if character == '/' && next == Some('*') {
+            index += 2;
+
+            while index < characters.len() && !(characters[index].1 == '*' && characters.get(index + 1).map(|(_, character)| *character) == Some('/')) {
+                index += 1;
+            }
+
+            index += 2;
+            continue;
+        }
+
+        if character == '"' || character == '\'' || character == '`' {
+            let mut value = String::new();
+
+            index += 1;
+
+            loop {
+                match characters.get(index) {
+                    Some((_, '\\')) => {
+                        if let Some((_, escaped)) = characters.get(index + 1) {
+                            value.push(*escaped);
+                        }
+
+                        index += 2;
+                    },
+
+                    Some((_, quote)) if *quote == character => break,
+
+                    Some((_, other)) => {
+                        value.push(*other);
+                        index += 1;
+                    },
+
+                    None => return Err(DeclarationError::UnterminatedString { offset: offset as u32 }),
+                }
+            }
+
+            tokens.push((Token::StringLiteral(value), offset as u32));
+            index += 1;
+            continue;
+        }
+
+        if character.is_ascii_digit() {
+            let mut value = String::new();
+
+            while let Some((_, digit)) = characters.get(index) {
+                if !(digit.is_ascii_alphanumeric() || *digit == '.' || *digit == '_') {
+                    break;
+                }
+
+                value.push(*digit);
+                index += 1;
+            }
+
+            tokens.push((Token::NumberLiteral(value), offset as u32));
+            continue;
+        }
+
+        if character.is_alphabetic() || character == '_' || character == '$' {
+            let mut name = String::new();
+
+            while let Some((_, part)) = characters.get(index) {
+                if !(part.is_alphanumeric() || *part == '_' || *part == '$') {
+                    break;
+                }
+
+                name.push(*part);
+                index += 1;
+            }
+
+            tokens.push((Token::Identifier(name), offset as u32));
+            continue;
+        }
+
+        if character == '=' && next == Some('>') {
+            tokens.push((Token::Arrow, offset as u32));
+            index += 2;
+            continue;
+        }
+
+        if character == '.' && next == Some('.') && characters.get(index + 2).map(|(_, character)| *character) == Some('.') {
+            tokens.push((Token::Ellipsis, offset as u32));
+            index += 3;
+            continue;
+        }
+
+        tokens.push((Token::Punctuator(character), offset as u32));
+        index += 1;
+    }
+
+    tokens.push((Token::End, source.len() as u32));
+
+    Ok(tokens)
+}
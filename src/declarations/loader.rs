@@ -0,0 +1,469 @@
+use std::collections::{ HashMap, HashSet };
+use std::sync::Arc;
+use failure::Error;
+
+use dynamic_typing::{
+    Type, ObjectType, FunctionType, FunctionKind, Variable, VariableKind, CustomTypeObject, MutexRef,
+    CustomType, Location, Scoped, ScopeRef, SafeBorrow, new_mutex_ref
+};
+use statics::OBJECT_PROTOTYPE;
+use arrays::array_of;
+use classes::instance_of;
+use super::parser::{ Declaration, Member, Parameter, TypeExpression, parse_declarations };
+
+// the bindings a declaration file contributes, `declare module "name"` blocks are kept apart
+// until an import asks for them
+pub struct Declarations {
+    pub globals: Vec<Variable>,
+    pub modules: HashMap<String, Type>,
+
+    // the binding named by a top level `export =`, which is what a package typed by the file exports
+    pub exported: Option<Type>,
+}
+
+// names of interfaces, classes and aliases share one namespace per file, qualified references
+// like `Express.Request` are looked up by their last segment
+pub fn load_declarations(source: &str, scope: &ScopeRef) -> Result<Declarations, Error> {
+    let declarations = parse_declarations(source)?;
+    let mut loader = Loader {
+        scope,
+        interfaces: HashMap::new(),
+        interface_bases: HashMap::new(),
+        classes: HashMap::new(),
+        aliases: HashMap::new(),
+        type_arguments: vec!(),
+        resolving: HashSet::new(),
+    };
+
+    loader.collect(&declarations);
+    loader.fill(&declarations);
+
+    let mut done = HashSet::new();
+    let names: Vec<String> = loader.interfaces.keys().cloned().collect();
+
+    for name in names {
+        loader.inherit_interface(&name, &mut done);
+    }
+
+    let mut modules = HashMap::new();
+    let globals = loader.bind(&declarations, &mut modules);
+    let exported = export_assignment(&declarations)
+        .and_then(|exported| globals.iter().find(|(bound, ..)| bound == exported))
+        .map(|(_, exported_type, _)| exported_type.clone());
+
+    let globals = globals.into_iter()
+        .map(|(name, binding_type, kind)| {
+            let mut variable = Variable::new(name, binding_type.clone(), kind);
+
+            variable.set_declared_type(binding_type);
+            variable
+        })
+        .collect();
+
+    Ok(Declarations { globals, modules, exported })
+}
+
+struct Loader<'a> {
+    scope: &'a ScopeRef,
+    interfaces: HashMap<String, MutexRef<ObjectType>>,
+    interface_bases: HashMap<String, Vec<TypeExpression>>,
+    // the constructor and prototype of each declared class
+    classes: HashMap<String, (MutexRef<FunctionType>, MutexRef<ObjectType>)>,
+    aliases: HashMap<String, (Vec<String>, TypeExpression)>,
+    // the type arguments of the aliases that are being instantiated, other type parameters are unknown
+    type_arguments: Vec<HashMap<String, Type>>,
+    // aliases that are currently being resolved, to stop recursive definitions
+    resolving: HashSet<String>,
+}
+
+impl<'a> Loader<'a> {
+    // creates the named types first, so declarations can reference each other in any order
+    fn collect(&mut self, declarations: &[Declaration]) {
+        for declaration in declarations {
+            match declaration {
+                Declaration::Interface { name, extends, .. } => {
+                    self.interface_bases.entry(name.clone()).or_insert_with(Vec::new).extend(extends.iter().cloned());
+
+                    if !self.interfaces.contains_key(name) {
+                        let interface = ObjectType::new(Some(name.clone()), HashMap::new(), Some(CustomTypeObject::from(&*OBJECT_PROTOTYPE)));
+
+                        self.interfaces.insert(name.clone(), new_mutex_ref(interface));
+                    }
+                },
+
+                Declaration::Class { name, .. } => {
+                    let prototype = new_mutex_ref(ObjectType::new(
+                        Some(format!("{}Prototype", name)),
+                        HashMap::new(),
+                        Some(CustomTypeObject::from(&*OBJECT_PROTOTYPE))
+                    ));
+                    let mut constructor = FunctionType::new(vec!());
+
+                    constructor.assign_name(name.clone());
+                    constructor.set_kind(FunctionKind::Class);
+
+                    let constructor = new_mutex_ref(constructor);
+
+                    prototype.borrow_mut_safe(|prototype| {
+                        prototype.properties.insert(String::from("constructor"), Type::from(&constructor));
+                    });
+
+                    constructor.borrow_mut_safe(|constructor| {
+                        constructor.properties.insert(String::from("prototype"), Type::from(&prototype));
+                    });
+
+                    self.classes.insert(name.clone(), (constructor, prototype));
+                },
+
+                Declaration::Alias { name, type_parameters, aliased } => {
+                    self.aliases.insert(name.clone(), (type_parameters.clone(), aliased.clone()));
+                },
+
+                Declaration::Namespace { declarations, .. } | Declaration::Module { declarations, .. } => self.collect(declarations),
+
+                _ => (),
+            }
+        }
+    }
+
+    fn fill(&mut self, declarations: &[Declaration]) {
+        for declaration in declarations {
+            match declaration {
+                Declaration::Interface { name, members, .. } => {
+                    let properties = self.members(members, false);
+
+                    self.interfaces[name].borrow_mut_safe(|interface| interface.properties.extend(properties));
+                },
+
+                Declaration::Class { name, extends, members } => {
+                    let (constructor, prototype) = self.classes[name].clone();
+                    let instance_members = self.members(members, false);
+                    let static_members = self.members(members, true);
+                    let arguments = members.iter()
+                        .filter_map(|member| match member {
+                            Member::Constructor { parameters } => Some(self.arguments(parameters)),
+                            _ => None,
+                        })
+                        .next()
+                        .unwrap_or_default();
+                    let parent_prototype = extends.as_ref().and_then(|extends| self.parent_prototype(extends));
+
+                    prototype.borrow_mut_safe(|prototype| {
+                        prototype.properties.extend(instance_members);
+
+                        if parent_prototype.is_some() {
+                            prototype.set_prototype(parent_prototype);
+                        }
+                    });
+
+                    constructor.borrow_mut_safe(|constructor| {
+                        constructor.properties.extend(static_members);
+                        constructor.set_arguments(arguments);
+                    });
+                },
+
+                Declaration::Namespace { declarations, .. } | Declaration::Module { declarations, .. } => self.fill(declarations),
+
+                _ => (),
+            }
+        }
+    }
+
+    // interfaces are structural, so extending one copies the members that are not overridden,
+    // bases are completed first so members are inherited across several levels
+    fn inherit_interface(&mut self, name: &str, done: &mut HashSet<String>) {
+        if !done.insert(name.to_owned()) {
+            return;
+        }
+
+        let bases = self.interface_bases.get(name).cloned().unwrap_or_default();
+
+        for base in bases.iter() {
+            if let TypeExpression::Reference { name: base_name, .. } = base {
+                if self.interfaces.contains_key(last_segment(base_name)) {
+                    self.inherit_interface(last_segment(base_name), done);
+                }
+            }
+
+            let inherited = match self.resolve(base) {
+                Type::Object(ref base) if !Arc::ptr_eq(base, &self.interfaces[name]) => {
+                    base.borrow_safe(|base| base.properties.clone())
+                },
+                _ => continue,
+            };
+
+            self.interfaces[name].borrow_mut_safe(|interface| {
+                for (property, property_type) in inherited {
+                    interface.properties.entry(property).or_insert(property_type);
+                }
+            });
+        }
+    }
+
+    fn parent_prototype(&mut self, extends: &TypeExpression) -> Option<CustomTypeObject> {
+        let name = match extends {
+            TypeExpression::Reference { name, .. } => last_segment(name).to_owned(),
+            _ => return None,
+        };
+
+        if let Some((_, prototype)) = self.classes.get(&name) {
+            return Some(CustomTypeObject::from(prototype));
+        }
+
+        let constructor = self.scope.locate(&name).ok()?;
+        let prototype = constructor.borrow_safe(|constructor| constructor.current_type().query_property("prototype", &Location::latest()))?;
+
+        match prototype {
+            Type::Object(ref prototype) => Some(CustomTypeObject::from(prototype)),
+            _ => None,
+        }
+    }
+
+    // the value bindings of a list of declarations, type-only declarations bind nothing
+    fn bind(&mut self, declarations: &[Declaration], modules: &mut HashMap<String, Type>) -> Vec<(String, Type, VariableKind)> {
+        let mut bindings: Vec<(String, Type, VariableKind)> = vec!();
+
+        for declaration in declarations {
+            let binding = match declaration {
+                Declaration::Function { name, parameters, return_type } => {
+                    // overloads keep the first signature
+                    if bindings.iter().any(|(bound, ..)| bound == name) {
+                        continue;
+                    }
+
+                    let function = self.function(Some(name), parameters, return_type, FunctionKind::Function);
+
+                    (name.clone(), Type::from(function), VariableKind::Const)
+                },
+
+                Declaration::Class { name, .. } => (name.clone(), Type::from(&self.classes[name].0), VariableKind::Const),
+
+                Declaration::Variable { name, variable_type, is_const } => {
+                    let kind = if *is_const { VariableKind::Const } else { VariableKind::Let };
+
+                    (name.clone(), self.resolve(variable_type), kind)
+                },
+
+                Declaration::Enum { name, members } => {
+                    let members = members.iter().map(|member| (member.clone(), Type::Number)).collect();
+
+                    (name.clone(), Type::from(ObjectType::new(Some(name.clone()), members, Some(CustomTypeObject::from(&*OBJECT_PROTOTYPE)))), VariableKind::Const)
+                },
+
+                Declaration::Namespace { name, declarations } => {
+                    let members = self.bind(declarations, modules);
+
+                    (name.clone(), module_type(name, &members, declarations), VariableKind::Const)
+                },
+
+                Declaration::Module { name, declarations } => {
+                    let members = self.bind(declarations, modules);
+
+                    modules.insert(name.clone(), module_type(name, &members, declarations));
+                    continue;
+                },
+
+                Declaration::Interface { .. } | Declaration::Alias { .. } | Declaration::ExportAssignment { .. } => continue,
+            };
+
+            bindings.push(binding);
+        }
+
+        bindings
+    }
+
+    fn members(&mut self, members: &[Member], statics: bool) -> HashMap<String, Type> {
+        let mut properties = HashMap::new();
+
+        for member in members {
+            match member {
+                Member::Property { name, property_type, is_static } if *is_static == statics => {
+                    properties.insert(name.clone(), self.resolve(property_type));
+                },
+
+                Member::Method { name, parameters, return_type, is_static } if *is_static == statics => {
+                    if properties.contains_key(name) {
+                        continue;
+                    }
+
+                    let method = self.function(Some(name), parameters, return_type, FunctionKind::Method);
+
+                    properties.insert(name.clone(), Type::from(method));
+                },
+
+                _ => (),
+            }
+        }
+
+        properties
+    }
+
+    fn function(&mut self, name: Option<&String>, parameters: &[Parameter], return_type: &TypeExpression, kind: FunctionKind) -> FunctionType {
+        let mut function = FunctionType::new(self.arguments(parameters));
+
+        if let Some(name) = name {
+            function.assign_name(name.clone());
+        }
+
+        function.set_kind(kind);
+        function.set_return_type(self.resolve(return_type));
+
+        function
+    }
+
+    fn arguments(&mut self, parameters: &[Parameter]) -> Vec<Variable> {
+        parameters.iter()
+            .map(|parameter| {
+                let parameter_type = match parameter.parameter_type {
+                    // rest parameters are checked against their element type
+                    TypeExpression::Array(ref element) if parameter.rest => self.resolve(element),
+                    ref parameter_type => self.resolve(parameter_type),
+                };
+
+                Variable::new(parameter.name.clone(), parameter_type, VariableKind::Let)
+            })
+            .collect()
+    }
+
+    fn resolve(&mut self, expression: &TypeExpression) -> Type {
+        match expression {
+            TypeExpression::Reference { name, arguments } => self.resolve_reference(last_segment(name), arguments),
+
+            TypeExpression::Union(members) => {
+                let members = members.iter().map(|member| self.resolve(member)).collect();
+
+                Type::union(members)
+            },
+
+            // the properties of all object members are merged, other members can't be combined
+            TypeExpression::Intersection(members) => {
+                let members: Vec<Type> = members.iter().map(|member| self.resolve(member)).collect();
+                let mut properties = HashMap::new();
+
+                for member in members.iter() {
+                    match member {
+                        Type::Object(object) => object.borrow_safe(|object| properties.extend(object.properties.clone())),
+                        _ => return member.clone(),
+                    }
+                }
+
+                Type::from(ObjectType::new(None, properties, Some(CustomTypeObject::from(&*OBJECT_PROTOTYPE))))
+            },
+
+            TypeExpression::Array(element) => array_of(self.resolve(element)),
+
+            TypeExpression::Tuple(elements) => {
+                let elements = elements.iter().map(|element| self.resolve(element)).collect();
+
+                array_of(Type::union(elements))
+            },
+
+            TypeExpression::Object(members) => {
+                let properties = self.members(members, false);
+
+                Type::from(ObjectType::new(None, properties, Some(CustomTypeObject::from(&*OBJECT_PROTOTYPE))))
+            },
+
+            TypeExpression::Function { parameters, return_type } => {
+                Type::from(self.function(None, parameters, return_type, FunctionKind::Arrow))
+            },
+
+            TypeExpression::StringLiteral => Type::String,
+            TypeExpression::NumberLiteral => Type::Number,
+            TypeExpression::BooleanLiteral => Type::Boolean,
+
+            TypeExpression::Parameter(name) => self.type_arguments.last()
+                .and_then(|arguments| arguments.get(name).cloned())
+                .unwrap_or(Type::Undefined),
+            TypeExpression::Unknown => Type::Undefined,
+        }
+    }
+
+    fn resolve_reference(&mut self, name: &str, arguments: &[TypeExpression]) -> Type {
+        match name {
+            "string" | "String" => return Type::String,
+            "number" | "Number" => return Type::Number,
+            "boolean" | "Boolean" => return Type::Boolean,
+            "RegExp" => return Type::RegExp,
+            "null" => return Type::Null,
+
+            // undefined doubles as the unknown type
+            "any" | "unknown" | "undefined" | "void" | "never" | "symbol" | "bigint" => return Type::Undefined,
+
+            "object" | "Object" => return Type::from(ObjectType::new(None, HashMap::new(), Some(CustomTypeObject::from(&*OBJECT_PROTOTYPE)))),
+
+            "Array" | "ReadonlyArray" => {
+                let element_type = arguments.first().map(|argument| self.resolve(argument)).unwrap_or(Type::Undefined);
+
+                return array_of(element_type);
+            },
+
+            _ => (),
+        }
+
+        if let Some(interface) = self.interfaces.get(name) {
+            return Type::from(interface);
+        }
+
+        if let Some((_, prototype)) = self.classes.get(name) {
+            return Type::from(ObjectType::new(Some(name.to_owned()), HashMap::new(), Some(CustomTypeObject::from(prototype))));
+        }
+
+        if let Some((type_parameters, aliased)) = self.aliases.get(name).cloned() {
+            if !self.resolving.insert(name.to_owned()) {
+                return Type::Undefined;
+            }
+
+            let type_arguments = type_parameters.into_iter()
+                .zip(arguments.iter().map(|argument| self.resolve(argument)).collect::<Vec<Type>>())
+                .collect();
+
+            self.type_arguments.push(type_arguments);
+
+            let alias_type = self.resolve(&aliased);
+
+            self.type_arguments.pop();
+            self.resolving.remove(name);
+
+            return alias_type;
+        }
+
+        // built-in classes like Date or Promise describe their instances
+        match self.scope.locate(name) {
+            Ok(variable) => {
+                let constructor_type = variable.borrow_safe(|variable| variable.current_type().clone());
+
+                instance_of(&constructor_type).unwrap_or(Type::Undefined)
+            },
+
+            Err(_) => Type::Undefined,
+        }
+    }
+}
+
+// a module is either the binding it assigns to `export =`, or a namespace of all its bindings
+fn export_assignment(declarations: &[Declaration]) -> Option<&String> {
+    declarations.iter()
+        .filter_map(|declaration| match declaration {
+            Declaration::ExportAssignment { name } => Some(name),
+            _ => None,
+        })
+        .next()
+}
+
+fn module_type(name: &str, bindings: &[(String, Type, VariableKind)], declarations: &[Declaration]) -> Type {
+    if let Some(exported) = export_assignment(declarations) {
+        if let Some((_, exported_type, _)) = bindings.iter().find(|(bound, ..)| bound == exported) {
+            return exported_type.clone();
+        }
+    }
+
+    let members = bindings.iter()
+        .map(|(name, binding_type, _)| (name.clone(), binding_type.clone()))
+        .collect();
+    Type::from(ObjectType::new(Some(name.to_owned()), members, Some(CustomTypeObject::from(&*OBJECT_PROTOTYPE))))
+}
+
+fn last_segment(name: &str) -> &str {
+    name.rsplit('.').next().unwrap_or(name)
+}
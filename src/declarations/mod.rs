@@ -0,0 +1,7 @@
+mod emitter;
+mod lexer;
+mod loader;
+mod parser;
+
+pub use self::emitter::{ emit_declarations, emit_module_declarations, emit_commonjs_declarations };
+pub use self::loader::load_declarations;
@@ -0,0 +1,959 @@
+use super::lexer::{ Token, tokenize };
+use error::DeclarationError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeExpression {
+    Reference { name: String, arguments: Vec<TypeExpression> },
+    Union(Vec<TypeExpression>),
+    Intersection(Vec<TypeExpression>),
+    Array(Box<TypeExpression>),
+    Tuple(Vec<TypeExpression>),
+    Object(Vec<Member>),
+    Function { parameters: Vec<Parameter>, return_type: Box<TypeExpression> },
+    StringLiteral,
+    NumberLiteral,
+    BooleanLiteral,
+    Parameter(String),
+    // `typeof`, `keyof`, indexed access and everything else we can't model
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    pub parameter_type: TypeExpression,
+    pub rest: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Member {
+    Property { name: String, property_type: TypeExpression, is_static: bool },
+    Method { name: String, parameters: Vec<Parameter>, return_type: TypeExpression, is_static: bool },
+    Constructor { parameters: Vec<Parameter> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Declaration {
+    Interface { name: String, extends: Vec<TypeExpression>, members: Vec<Member> },
+    Alias { name: String, type_parameters: Vec<String>, aliased: TypeExpression },
+    Function { name: String, parameters: Vec<Parameter>, return_type: TypeExpression },
+    Class { name: String, extends: Option<TypeExpression>, members: Vec<Member> },
+    Variable { name: String, variable_type: TypeExpression, is_const: bool },
+    Namespace { name: String, declarations: Vec<Declaration> },
+    Module { name: String, declarations: Vec<Declaration> },
+    Enum { name: String, members: Vec<String> },
+    // `export = name` and `export default name`
+    ExportAssignment { name: String },
+}
+
+// modifiers are only treated as such when a member name follows them, `readonly: boolean` is a property
+const MODIFIERS: [&str; 8] = ["public", "private", "protected", "static", "readonly", "abstract", "declare", "override"];
+
+pub fn parse_declarations(source: &str) -> Result<Vec<Declaration>, DeclarationError> {
+    let mut parser = Parser { tokens: tokenize(source)?, position: 0, type_parameters: vec!() };
+
+    parser.declaration_list(true)
+}
+
+struct Parser {
+    tokens: Vec<(Token, u32)>,
+    position: usize,
+    // names of the type parameters in scope
+    type_parameters: Vec<Vec<String>>,
+}
+
+impl Parser {
+    fn current(&self) -> &Token {
+        &self.tokens[self.position].0
+    }
+
+    fn peek(&self, distance: usize) -> &Token {
+        let index = (self.position + distance).min(self.tokens.len() - 1);
+
+        &self.tokens[index].0
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.current().clone();
+
+        if self.position < self.tokens.len() - 1 {
+            self.position += 1;
+        }
+
+        token
+    }
+
+    fn eat(&mut self, punctuator: char) -> bool {
+        if self.current().is_punctuator(punctuator) {
+            self.advance();
+            return true;
+        }
+
+        false
+    }
+
+    fn eat_identifier(&mut self, identifier: &str) -> bool {
+        if self.current().is_identifier(identifier) {
+            self.advance();
+            return true;
+        }
+
+        false
+    }
+
+    fn unexpected(&self, expected: &str) -> DeclarationError {
+        let (ref found, offset) = self.tokens[self.position];
+
+        DeclarationError::UnexpectedToken { expected: expected.to_owned(), found: found.to_string(), offset }
+    }
+
+    fn expect(&mut self, punctuator: char) -> Result<(), DeclarationError> {
+        if self.eat(punctuator) {
+            return Ok(());
+        }
+
+        Err(self.unexpected(&punctuator.to_string()))
+    }
+
+    fn name(&mut self) -> Result<String, DeclarationError> {
+        match self.current().clone() {
+            Token::Identifier(name) => {
+                self.advance();
+                Ok(name)
+            },
+
+            _ => Err(self.unexpected("identifier")),
+        }
+    }
+
+    fn at_end_of_block(&self, top_level: bool) -> bool {
+        match self.current() {
+            Token::End => true,
+            token => !top_level && token.is_punctuator('}'),
+        }
+    }
+
+    fn declaration_list(&mut self, top_level: bool) -> Result<Vec<Declaration>, DeclarationError> {
+        let mut declarations = vec!();
+
+        while !self.at_end_of_block(top_level) {
+            if self.eat(';') {
+                continue;
+            }
+
+            // a closing brace without an opening one is never consumed by a declaration
+            if top_level && self.current().is_punctuator('}') {
+                return Err(self.unexpected("declaration"));
+            }
+
+            declarations.extend(self.declaration()?);
+        }
+
+        Ok(declarations)
+    }
+
+    fn declaration(&mut self) -> Result<Vec<Declaration>, DeclarationError> {
+        if self.current().is_identifier("import") {
+            self.skip_statement();
+            return Ok(vec!());
+        }
+
+        if self.eat_identifier("export") {
+            if self.eat('=') {
+                let name = self.name()?;
+
+                self.eat(';');
+                return Ok(vec!(Declaration::ExportAssignment { name }));
+            }
+
+            // re-exports only matter once modules are resolved
+            if self.current().is_punctuator('{') || self.current().is_punctuator('*') || self.current().is_identifier("as") {
+                self.skip_statement();
+                return Ok(vec!());
+            }
+
+            if self.eat_identifier("default") {
+                if let Token::Identifier(name) = self.current().clone() {
+                    if self.peek(1).is_punctuator(';') || self.peek(1) == &Token::End {
+                        self.advance();
+                        self.eat(';');
+
+                        return Ok(vec!(Declaration::ExportAssignment { name }));
+                    }
+                }
+            }
+        }
+
+        self.eat_identifier("declare");
+
+        let keyword = match self.current().clone() {
+            Token::Identifier(keyword) => keyword,
+            _ => {
+                self.skip_statement();
+                return Ok(vec!());
+            },
+        };
+
+        match keyword.as_str() {
+            "interface" => {
+                self.advance();
+                self.interface().map(|declaration| vec!(declaration))
+            },
+
+            "type" if self.peek(1) != &Token::Punctuator('=') => {
+                self.advance();
+
+                let name = self.name()?;
+                let type_parameters = self.type_parameter_list()?;
+
+                self.expect('=')?;
+
+                let aliased = self.type_expression()?;
+
+                self.type_parameters.pop();
+                self.eat(';');
+
+                Ok(vec!(Declaration::Alias { name, type_parameters, aliased }))
+            },
+
+            "function" => {
+                self.advance();
+
+                let name = self.name()?;
+                let (parameters, return_type) = self.signature()?;
+
+                self.eat(';');
+
+                Ok(vec!(Declaration::Function { name, parameters, return_type }))
+            },
+
+            "abstract" | "class" => {
+                self.eat_identifier("abstract");
+                self.advance();
+                self.class().map(|declaration| vec!(declaration))
+            },
+
+            "const" | "let" | "var" => {
+                self.advance();
+
+                if self.eat_identifier("enum") {
+                    return self.enumeration().map(|declaration| vec!(declaration));
+                }
+
+                self.variables(keyword == "const")
+            },
+
+            "enum" => {
+                self.advance();
+                self.enumeration().map(|declaration| vec!(declaration))
+            },
+
+            "namespace" | "module" => {
+                self.advance();
+                self.namespace().map(|declaration| vec!(declaration))
+            },
+
+            // the content of `declare global` is part of the global scope anyway
+            "global" if self.peek(1).is_punctuator('{') => {
+                self.advance();
+                self.expect('{')?;
+
+                let declarations = self.declaration_list(false)?;
+
+                self.expect('}')?;
+
+                Ok(declarations)
+            },
+
+            _ => {
+                self.skip_statement();
+                Ok(vec!())
+            },
+        }
+    }
+
+    fn interface(&mut self) -> Result<Declaration, DeclarationError> {
+        let name = self.name()?;
+        let mut extends = vec!();
+
+        self.type_parameter_list()?;
+
+        if self.eat_identifier("extends") {
+            loop {
+                extends.push(self.type_expression()?);
+
+                if !self.eat(',') {
+                    break;
+                }
+            }
+        }
+
+        self.expect('{')?;
+
+        let members = self.members()?;
+
+        self.expect('}')?;
+        self.type_parameters.pop();
+
+        Ok(Declaration::Interface { name, extends, members })
+    }
+
+    fn class(&mut self) -> Result<Declaration, DeclarationError> {
+        let name = self.name()?;
+        let mut extends = None;
+
+        self.type_parameter_list()?;
+
+        if self.eat_identifier("extends") {
+            extends = Some(self.type_expression()?);
+        }
+
+        if self.eat_identifier("implements") {
+            loop {
+                self.type_expression()?;
+
+                if !self.eat(',') {
+                    break;
+                }
+            }
+        }
+
+        self.expect('{')?;
+
+        let members = self.members()?;
+
+        self.expect('}')?;
+        self.type_parameters.pop();
+
+        Ok(Declaration::Class { name, extends, members })
+    }
+
+    fn variables(&mut self, is_const: bool) -> Result<Vec<Declaration>, DeclarationError> {
+        let mut declarations = vec!();
+
+        loop {
+            let name = self.name()?;
+            let variable_type = if self.eat(':') { self.type_expression()? } else { TypeExpression::Unknown };
+
+            // initializers of ambient constants are literals at most
+            if self.eat('=') {
+                self.advance();
+            }
+
+            declarations.push(Declaration::Variable { name, variable_type, is_const });
+
+            if !self.eat(',') {
+                break;
+            }
+        }
+
+        self.eat(';');
+
+        Ok(declarations)
+    }
+
+    fn enumeration(&mut self) -> Result<Declaration, DeclarationError> {
+        let name = self.name()?;
+        let mut members = vec!();
+
+        self.expect('{')?;
+
+        while !self.current().is_punctuator('}') && self.current() != &Token::End {
+            match self.advance() {
+                Token::Identifier(member) | Token::StringLiteral(member) => members.push(member),
+                _ => (),
+            }
+
+            if self.eat('=') {
+                self.skip_until(&[',', '}']);
+            }
+
+            self.eat(',');
+        }
+
+        self.expect('}')?;
+
+        Ok(Declaration::Enum { name, members })
+    }
+
+    fn namespace(&mut self) -> Result<Declaration, DeclarationError> {
+        if let Token::StringLiteral(name) = self.current().clone() {
+            self.advance();
+
+            // `declare module "name";` is a shorthand for a module of unknown shape
+            if !self.current().is_punctuator('{') {
+                self.eat(';');
+                return Ok(Declaration::Module { name, declarations: vec!() });
+            }
+
+            self.expect('{')?;
+
+            let declarations = self.declaration_list(false)?;
+
+            self.expect('}')?;
+
+            return Ok(Declaration::Module { name, declarations });
+        }
+
+        let name = self.name()?;
+
+        // `namespace a.b {}` is a namespace b nested in a namespace a
+        if self.eat('.') {
+            let inner = self.namespace()?;
+
+            return Ok(Declaration::Namespace { name, declarations: vec!(inner) });
+        }
+
+        self.expect('{')?;
+
+        let declarations = self.declaration_list(false)?;
+
+        self.expect('}')?;
+
+        Ok(Declaration::Namespace { name, declarations })
+    }
+
+    // the members of interfaces, classes and object types, up to the closing brace
+    fn members(&mut self) -> Result<Vec<Member>, DeclarationError> {
+        let mut members = vec!();
+
+        while !self.current().is_punctuator('}') && self.current() != &Token::End {
+            if self.eat(';') || self.eat(',') {
+                continue;
+            }
+
+            if let Some(member) = self.member()? {
+                members.push(member);
+            }
+        }
+
+        Ok(members)
+    }
+
+    fn member(&mut self) -> Result<Option<Member>, DeclarationError> {
+        let mut is_static = false;
+
+        while let Token::Identifier(modifier) = self.current().clone() {
+            if !MODIFIERS.contains(&modifier.as_str()) || !self.starts_member_name(1) {
+                break;
+            }
+
+            is_static = is_static || modifier == "static";
+            self.advance();
+        }
+
+        // index, call and construct signatures have no name we could look up
+        if self.current().is_punctuator('[') || self.current().is_punctuator('(') || self.current().is_punctuator('<')
+            || (self.current().is_identifier("new") && (self.peek(1).is_punctuator('(') || self.peek(1).is_punctuator('<'))) {
+            self.skip_member();
+            return Ok(None);
+        }
+
+        let accessor = match self.current() {
+            Token::Identifier(accessor) if (accessor == "get" || accessor == "set") && self.starts_member_name(1) => Some(accessor.clone()),
+            _ => None,
+        };
+
+        if accessor.is_some() {
+            self.advance();
+        }
+
+        let name = match self.advance() {
+            Token::Identifier(name) | Token::StringLiteral(name) | Token::NumberLiteral(name) => name,
+            _ => return Err(self.unexpected("member name")),
+        };
+
+        self.eat('?');
+        self.eat('!');
+
+        if name == "constructor" && self.current().is_punctuator('(') {
+            let (parameters, _) = self.signature()?;
+
+            return Ok(Some(Member::Constructor { parameters }));
+        }
+
+        if self.current().is_punctuator('(') || self.current().is_punctuator('<') {
+            let (parameters, return_type) = self.signature()?;
+
+            // accessors look like properties from the outside
+            return Ok(Some(match accessor.as_ref().map(|accessor| accessor.as_str()) {
+                Some("get") => Member::Property { name, property_type: return_type, is_static },
+                Some("set") => {
+                    let property_type = parameters.into_iter().next()
+                        .map(|parameter| parameter.parameter_type)
+                        .unwrap_or(TypeExpression::Unknown);
+
+                    Member::Property { name, property_type, is_static }
+                },
+                _ => Member::Method { name, parameters, return_type, is_static },
+            }));
+        }
+
+        let property_type = if self.eat(':') { self.type_expression()? } else { TypeExpression::Unknown };
+
+        if self.eat('=') {
+            self.skip_until(&[';', ',', '}']);
+        }
+
+        Ok(Some(Member::Property { name, property_type, is_static }))
+    }
+
+    fn starts_member_name(&self, distance: usize) -> bool {
+        match self.peek(distance) {
+            Token::Identifier(_) | Token::StringLiteral(_) | Token::NumberLiteral(_) => true,
+            token => token.is_punctuator('['),
+        }
+    }
+
+    // `<T>(a: T, b?: string): T`, the return type defaults to unknown
+    fn signature(&mut self) -> Result<(Vec<Parameter>, TypeExpression), DeclarationError> {
+        self.type_parameter_list()?;
+
+        let parameters = self.parameters()?;
+        let return_type = if self.eat(':') { self.type_expression()? } else { TypeExpression::Unknown };
+
+        self.type_parameters.pop();
+
+        Ok((parameters, return_type))
+    }
+
+    fn parameters(&mut self) -> Result<Vec<Parameter>, DeclarationError> {
+        let mut parameters = vec!();
+
+        self.expect('(')?;
+
+        while !self.eat(')') {
+            while let Token::Identifier(modifier) = self.current().clone() {
+                if !MODIFIERS.contains(&modifier.as_str()) || !self.starts_member_name(1) {
+                    break;
+                }
+
+                self.advance();
+            }
+
+            let rest = match self.current() {
+                Token::Ellipsis => { self.advance(); true },
+                _ => false,
+            };
+
+            let name = match self.current().clone() {
+                Token::Identifier(name) => { self.advance(); name },
+
+                // destructured parameters are only known by their position
+                _ => {
+                    self.skip_balanced();
+                    format!("arg{}", parameters.len())
+                },
+            };
+
+            self.eat('?');
+
+            let parameter_type = if self.eat(':') { self.type_expression()? } else { TypeExpression::Unknown };
+
+            if self.eat('=') {
+                self.skip_until(&[',', ')']);
+            }
+
+            parameters.push(Parameter { name, parameter_type, rest });
+
+            if !self.eat(',') {
+                self.expect(')')?;
+                break;
+            }
+        }
+
+        Ok(parameters)
+    }
+
+    // always pushes a (possibly empty) set of type parameters, callers pop it again
+    fn type_parameter_list(&mut self) -> Result<Vec<String>, DeclarationError> {
+        let mut names = vec!();
+
+        if self.eat('<') {
+            loop {
+                names.push(self.name()?);
+
+                if self.eat_identifier("extends") {
+                    self.type_expression()?;
+                }
+
+                if self.eat('=') {
+                    self.type_expression()?;
+                }
+
+                if !self.eat(',') {
+                    break;
+                }
+            }
+
+            self.expect('>')?;
+        }
+
+        self.type_parameters.push(names.clone());
+
+        Ok(names)
+    }
+
+    fn is_type_parameter(&self, name: &str) -> bool {
+        self.type_parameters.iter().any(|names| names.iter().any(|parameter| parameter == name))
+    }
+
+    fn type_expression(&mut self) -> Result<TypeExpression, DeclarationError> {
+        let checked = self.union()?;
+
+        // conditional types depend on their type arguments
+        if self.current().is_identifier("extends") && !self.peek(1).is_punctuator('{') {
+            self.advance();
+            self.union()?;
+            self.expect('?')?;
+            self.type_expression()?;
+            self.expect(':')?;
+            self.type_expression()?;
+
+            return Ok(TypeExpression::Unknown);
+        }
+
+        Ok(checked)
+    }
+
+    fn union(&mut self) -> Result<TypeExpression, DeclarationError> {
+        let mut members = vec!();
+
+        self.eat('|');
+
+        loop {
+            members.push(self.intersection()?);
+
+            if !self.eat('|') {
+                break;
+            }
+        }
+
+        Ok(match members.len() {
+            1 => members.remove(0),
+            _ => TypeExpression::Union(members),
+        })
+    }
+
+    fn intersection(&mut self) -> Result<TypeExpression, DeclarationError> {
+        let mut members = vec!();
+
+        self.eat('&');
+
+        loop {
+            members.push(self.postfix()?);
+
+            if !self.eat('&') {
+                break;
+            }
+        }
+
+        Ok(match members.len() {
+            1 => members.remove(0),
+            _ => TypeExpression::Intersection(members),
+        })
+    }
+
+    fn postfix(&mut self) -> Result<TypeExpression, DeclarationError> {
+        let mut expression = self.primary()?;
+
+        while self.current().is_punctuator('[') {
+            self.advance();
+
+            if self.eat(']') {
+                expression = TypeExpression::Array(Box::new(expression));
+                continue;
+            }
+
+            // indexed access types like `T["key"]`
+            self.type_expression()?;
+            self.expect(']')?;
+            expression = TypeExpression::Unknown;
+        }
+
+        Ok(expression)
+    }
+
+    fn primary(&mut self) -> Result<TypeExpression, DeclarationError> {
+        match self.current().clone() {
+            Token::Punctuator('(') => {
+                if self.is_function_type() {
+                    return self.function_type();
+                }
+
+                self.advance();
+
+                let expression = self.type_expression()?;
+
+                self.expect(')')?;
+
+                Ok(expression)
+            },
+
+            Token::Punctuator('<') => self.function_type(),
+
+            Token::Punctuator('{') => {
+                self.advance();
+
+                let members = self.members()?;
+
+                self.expect('}')?;
+
+                Ok(TypeExpression::Object(members))
+            },
+
+            Token::Punctuator('[') => {
+                let mut elements = vec!();
+
+                self.advance();
+
+                while !self.eat(']') {
+                    if let Token::Ellipsis = self.current() {
+                        self.advance();
+                    }
+
+                    // labeled tuple members, `[name: string]`
+                    if let Token::Identifier(_) = self.current() {
+                        if self.peek(1).is_punctuator(':') || self.peek(1).is_punctuator('?') && self.peek(2).is_punctuator(':') {
+                            self.advance();
+                            self.eat('?');
+                            self.advance();
+                        }
+                    }
+
+                    elements.push(self.type_expression()?);
+                    self.eat('?');
+
+                    if !self.eat(',') {
+                        self.expect(']')?;
+                        break;
+                    }
+                }
+
+                Ok(TypeExpression::Tuple(elements))
+            },
+
+            Token::Punctuator('-') => {
+                self.advance();
+                self.advance();
+
+                Ok(TypeExpression::NumberLiteral)
+            },
+
+            Token::StringLiteral(_) => {
+                self.advance();
+                Ok(TypeExpression::StringLiteral)
+            },
+
+            Token::NumberLiteral(_) => {
+                self.advance();
+                Ok(TypeExpression::NumberLiteral)
+            },
+
+            Token::Identifier(name) => {
+                self.advance();
+
+                match name.as_str() {
+                    "true" | "false" => Ok(TypeExpression::BooleanLiteral),
+
+                    "new" if self.current().is_punctuator('(') || self.current().is_punctuator('<') => self.function_type(),
+
+                    "typeof" => {
+                        self.qualified_name()?;
+                        Ok(TypeExpression::Unknown)
+                    },
+
+                    "keyof" | "unique" | "infer" => {
+                        self.postfix()?;
+                        Ok(TypeExpression::Unknown)
+                    },
+
+                    "readonly" => self.postfix(),
+
+                    _ => self.reference(name),
+                }
+            },
+
+            _ => Err(self.unexpected("type")),
+        }
+    }
+
+    fn reference(&mut self, name: String) -> Result<TypeExpression, DeclarationError> {
+        let mut name = name;
+
+        while self.current().is_punctuator('.') {
+            self.advance();
+            name = format!("{}.{}", name, self.name()?);
+        }
+
+        let mut arguments = vec!();
+
+        if self.eat('<') {
+            loop {
+                arguments.push(self.type_expression()?);
+
+                if !self.eat(',') {
+                    break;
+                }
+            }
+
+            self.expect('>')?;
+        }
+
+        // type predicates like `value is string` are booleans at runtime
+        if self.current().is_identifier("is") {
+            self.advance();
+            self.type_expression()?;
+
+            return Ok(TypeExpression::BooleanLiteral);
+        }
+
+        if self.is_type_parameter(&name) {
+            return Ok(TypeExpression::Parameter(name));
+        }
+
+        Ok(TypeExpression::Reference { name, arguments })
+    }
+
+    fn qualified_name(&mut self) -> Result<String, DeclarationError> {
+        let mut name = self.name()?;
+
+        while self.eat('.') {
+            name = format!("{}.{}", name, self.name()?);
+        }
+
+        Ok(name)
+    }
+
+    fn function_type(&mut self) -> Result<TypeExpression, DeclarationError> {
+        self.type_parameter_list()?;
+
+        let parameters = self.parameters()?;
+
+        if !self.current().is_punctuator(':') && self.current() != &Token::Arrow {
+            return Err(self.unexpected("=>"));
+        }
+
+        self.advance();
+
+        let return_type = self.type_expression()?;
+
+        self.type_parameters.pop();
+
+        Ok(TypeExpression::Function { parameters, return_type: Box::new(return_type) })
+    }
+
+    // a parenthesis starts a function type if the matching one is followed by an arrow
+    fn is_function_type(&self) -> bool {
+        let closing = self.tokens[self.position..].iter()
+            .scan(0, |depth, (token, _)| {
+                match token {
+                    Token::Punctuator('(') => *depth += 1,
+                    Token::Punctuator(')') => *depth -= 1,
+                    _ => (),
+                }
+
+                Some(*depth)
+            })
+            .position(|depth| depth == 0);
+
+        match closing {
+            Some(closing) => self.peek(closing + 1) == &Token::Arrow,
+            None => false,
+        }
+    }
+
+    // skips a single token, or a whole bracketed group if it starts one
+    fn skip_balanced(&mut self) {
+        let mut depth = 0;
+
+        loop {
+            match self.advance() {
+                Token::Punctuator('{') | Token::Punctuator('(') | Token::Punctuator('[') => depth += 1,
+                Token::Punctuator('}') | Token::Punctuator(')') | Token::Punctuator(']') => depth -= 1,
+                Token::End => return,
+                _ => (),
+            }
+
+            if depth <= 0 {
+                return;
+            }
+        }
+    }
+
+    fn skip_until(&mut self, terminators: &[char]) {
+        while !terminators.iter().any(|terminator| self.current().is_punctuator(*terminator)) && self.current() != &Token::End {
+            self.skip_balanced();
+        }
+    }
+
+    fn skip_member(&mut self) {
+        self.skip_until(&[';', ',', '}']);
+    }
+
+    // statements we don't understand end with a semicolon or a block
+    fn skip_statement(&mut self) {
+        while self.current() != &Token::End && !self.current().is_punctuator('}') {
+            if self.eat(';') {
+                return;
+            }
+
+            let is_block = self.current().is_punctuator('{');
+
+            self.skip_balanced();
+
+            if is_block && !self.current().is_identifier("from") {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(name: &str) -> TypeExpression {
+        TypeExpression::Reference { name: name.to_owned(), arguments: vec!() }
+    }
+
+    #[test]
+    fn parses_unions() {
+        let declarations = parse_declarations("declare let value: string | number | null;").unwrap();
+
+        assert_eq!(declarations, vec!(Declaration::Variable {
+            name: String::from("value"),
+            variable_type: TypeExpression::Union(vec!(reference("string"), reference("number"), reference("null"))),
+            is_const: false,
+        }));
+    }
+
+    #[test]
+    fn parses_generic_references_and_type_parameters() {
+        let declarations = parse_declarations("declare function first<T>(items: Array<T>): T;").unwrap();
+
+        assert_eq!(declarations, vec!(Declaration::Function {
+            name: String::from("first"),
+            parameters: vec!(Parameter {
+                name: String::from("items"),
+                parameter_type: TypeExpression::Reference { name: String::from("Array"), arguments: vec!(TypeExpression::Parameter(String::from("T"))) },
+                rest: false,
+            }),
+            return_type: TypeExpression::Parameter(String::from("T")),
+        }));
+    }
+
+    #[test]
+    fn parses_declared_modules() {
+        let declarations = parse_declarations("declare module \"left-pad\" {\n    export function pad(value: string): string;\n}").unwrap();
+
+        assert_eq!(declarations, vec!(Declaration::Module {
+            name: String::from("left-pad"),
+            declarations: vec!(Declaration::Function {
+                name: String::from("pad"),
+                parameters: vec!(Parameter { name: String::from("value"), parameter_type: reference("string"), rest: false }),
+                return_type: reference("string"),
+            }),
+        }));
+    }
+}
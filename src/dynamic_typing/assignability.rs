@@ -0,0 +1,120 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::{ Type, ObjectType, FunctionType, CustomType, MutexRef, SafeBorrow, Location };
+use statics::OBJECT_PROTOTYPE;
+
+// pairs of (target, value) ids that are already being compared, recursive shapes are assumed to match
+type Visited = HashSet<(Uuid, Uuid)>;
+
+impl Type {
+    // whether a value of type `value` can be stored where this type is expected
+    pub fn is_assignable_from(&self, value: &Type) -> bool {
+        assignable(self, value, &mut Visited::new())
+    }
+}
+
+fn assignable(target: &Type, value: &Type, visited: &mut Visited) -> bool {
+    if target == value {
+        return true;
+    }
+
+    match (target, value) {
+        // nothing is known about the target yet, or it was only initialized with null
+        (Type::Undefined, _) => true,
+        (Type::Null, _) => true,
+
+        (_, Type::Mixed(members)) => members.iter().all(|member| assignable(target, member, visited)),
+        (Type::Mixed(members), _) => members.iter().any(|member| assignable(member, value, visited)),
+
+        (Type::Composed { outer: target_outer, inner: target_inner }, Type::Composed { outer: value_outer, inner: value_inner }) => {
+            let same_container = Arc::ptr_eq(target_outer, value_outer)
+                || target_outer.borrow_safe(|outer| *outer.id()) == value_outer.borrow_safe(|outer| *outer.id());
+
+            same_container && assignable(target_inner, value_inner, visited)
+        },
+
+        (Type::Object(target_object), Type::Object(value_object)) => object_assignable(target_object, value_object, visited),
+        (Type::Function(target_function), Type::Function(value_function)) => function_assignable(target_function, value_function, visited),
+
+        _ => false,
+    }
+}
+
+fn object_assignable(target: &MutexRef<ObjectType>, value: &MutexRef<ObjectType>, visited: &mut Visited) -> bool {
+    let (target_id, target_prototype, target_properties) = target.borrow_safe(|target| {
+        (*target.id(), target.prototype().cloned(), target.properties.clone())
+    });
+
+    let value_id = value.borrow_safe(|value| *value.id());
+
+    if !visited.insert((target_id, value_id)) {
+        return true;
+    }
+
+    // instances of a subclass can be used wherever an instance of the base class is expected
+    let prototype_chain = prototype_chain(value);
+
+    if prototype_chain.contains(&target_id) {
+        return true;
+    }
+
+    // instances of a class can only be replaced by instances of that class or one of its subclasses,
+    // plain objects are compared by their shape
+    if let Some(target_prototype) = target_prototype {
+        if target_prototype.id() != OBJECT_PROTOTYPE.borrow_safe(|prototype| *prototype.id()) && !prototype_chain.contains(&target_prototype.id()) {
+            return false;
+        }
+    }
+
+    // otherwise the value needs at least every property of the target, extra properties are fine
+    let location = Location::latest();
+
+    target_properties.iter().all(|(name, property_type)| {
+        let value_property = value.borrow_safe(|value| value.query_property(name, &location));
+
+        match value_property {
+            Some(value_property) => assignable(property_type, &value_property, visited),
+            None => false,
+        }
+    })
+}
+
+fn function_assignable(target: &MutexRef<FunctionType>, value: &MutexRef<FunctionType>, visited: &mut Visited) -> bool {
+    let (target_arguments, target_return) = target.borrow_safe(|target| {
+        let arguments: Vec<Type> = target.arguments().iter().map(|argument| argument.current_type().clone()).collect();
+
+        (arguments, target.return_type(&vec!()))
+    });
+
+    let (value_arguments, value_return) = value.borrow_safe(|value| {
+        let arguments: Vec<Type> = value.arguments().iter().map(|argument| argument.current_type().clone()).collect();
+
+        (arguments, value.return_type(&vec!()))
+    });
+
+    // the value can not require more arguments than callers of the target pass
+    if value_arguments.len() > target_arguments.len() {
+        return false;
+    }
+
+    // parameters are contravariant, the value has to accept everything the target would be called with
+    let arguments_match = target_arguments.iter()
+        .zip(value_arguments.iter())
+        .all(|(target_argument, value_argument)| assignable(value_argument, target_argument, visited));
+
+    arguments_match && assignable(&target_return, &value_return, visited)
+}
+
+fn prototype_chain(object: &MutexRef<ObjectType>) -> Vec<Uuid> {
+    let mut chain = vec!();
+    let mut current = object.borrow_safe(|object| object.prototype().cloned());
+
+    while let Some(prototype) = current {
+        chain.push(prototype.id());
+        current = prototype.prototype();
+    }
+
+    chain
+}
@@ -37,6 +37,14 @@ impl<TC: PartialEq> ChangeTrace<TC> {
     pub fn new() -> Self {
         ChangeTrace { changes: vec!() }
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn attributes(&self) -> impl Iterator<Item = &TC> {
+        self.changes.iter().map(|change| &change.attribute)
+    }
 }
 
 #[derive(Debug, Serialize, PartialEq, Clone)]
@@ -70,6 +78,11 @@ pub struct Location {
 }
 
 impl Location {
+    // a location past every traced change, so properties are looked up in their final state
+    pub fn latest() -> Self {
+        Location { start: u32::max_value(), end: u32::max_value(), line: 0, column: 0 }
+    }
+
     pub fn collapse(mut self, after: bool) -> Self {
         if after {
             self.end += 1;
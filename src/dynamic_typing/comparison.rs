@@ -0,0 +1,119 @@
+use std::collections::{ HashMap, HashSet };
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::{ Type, ObjectType, FunctionType, CustomType, MutexRef, SafeBorrow };
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ComparisonMode {
+    // objects and functions are only equal to themselves
+    Identity,
+    // objects and functions are equal when they have the same shape
+    Structural,
+}
+
+// pairs of ids that are already being compared, recursive shapes are assumed to be equal
+type Visited = HashSet<(Uuid, Uuid)>;
+
+impl Type {
+    pub fn equals(&self, other: &Type, mode: ComparisonMode) -> bool {
+        equals(self, other, mode, &mut Visited::new())
+    }
+}
+
+fn equals(left: &Type, right: &Type, mode: ComparisonMode, visited: &mut Visited) -> bool {
+    match (left, right) {
+        (Type::Object(left), Type::Object(right)) => objects_equal(left, right, mode, visited),
+        (Type::Function(left), Type::Function(right)) => functions_equal(left, right, mode, visited),
+
+        (Type::Mixed(left), Type::Mixed(right)) => {
+            left.len() == right.len()
+                && left.iter().all(|member| right.iter().any(|other| equals(member, other, mode, visited)))
+        },
+
+        (Type::Composed { outer: left_outer, inner: left_inner }, Type::Composed { outer: right_outer, inner: right_inner }) => {
+            objects_equal(left_outer, right_outer, mode, visited) && equals(left_inner, right_inner, mode, visited)
+        },
+
+        (Type::Number, Type::Number) => true,
+        (Type::String, Type::String) => true,
+        (Type::Boolean, Type::Boolean) => true,
+        (Type::RegExp, Type::RegExp) => true,
+        (Type::Undefined, Type::Undefined) => true,
+        (Type::Null, Type::Null) => true,
+        _ => false,
+    }
+}
+
+fn same_identity<T: CustomType>(left: &MutexRef<T>, right: &MutexRef<T>) -> bool {
+    // comparing through the ids would lock the same mutex twice
+    Arc::ptr_eq(left, right) || left.borrow_safe(|left| *left.id()) == right.borrow_safe(|right| *right.id())
+}
+
+fn objects_equal(left: &MutexRef<ObjectType>, right: &MutexRef<ObjectType>, mode: ComparisonMode, visited: &mut Visited) -> bool {
+    if same_identity(left, right) {
+        return true;
+    }
+
+    if mode == ComparisonMode::Identity {
+        return false;
+    }
+
+    let (left_id, left_array, left_prototype, left_properties) = object_shape(left);
+    let (right_id, right_array, right_prototype, right_properties) = object_shape(right);
+
+    if !visited.insert((left_id, right_id)) {
+        return true;
+    }
+
+    left_array == right_array
+        && left_prototype == right_prototype
+        && properties_equal(&left_properties, &right_properties, mode, visited)
+}
+
+fn functions_equal(left: &MutexRef<FunctionType>, right: &MutexRef<FunctionType>, mode: ComparisonMode, visited: &mut Visited) -> bool {
+    if same_identity(left, right) {
+        return true;
+    }
+
+    if mode == ComparisonMode::Identity {
+        return false;
+    }
+
+    let (left_id, left_arguments, left_return, left_properties) = function_shape(left);
+    let (right_id, right_arguments, right_return, right_properties) = function_shape(right);
+
+    if !visited.insert((left_id, right_id)) {
+        return true;
+    }
+
+    left_arguments.len() == right_arguments.len()
+        && left_arguments.iter().zip(right_arguments.iter()).all(|(left, right)| equals(left, right, mode, visited))
+        && equals(&left_return, &right_return, mode, visited)
+        && properties_equal(&left_properties, &right_properties, mode, visited)
+}
+
+fn properties_equal(left: &HashMap<String, Type>, right: &HashMap<String, Type>, mode: ComparisonMode, visited: &mut Visited) -> bool {
+    left.len() == right.len() && left.iter().all(|(name, left_type)| {
+        match right.get(name) {
+            Some(right_type) => equals(left_type, right_type, mode, visited),
+            None => false,
+        }
+    })
+}
+
+fn object_shape(object: &MutexRef<ObjectType>) -> (Uuid, bool, Option<Uuid>, HashMap<String, Type>) {
+    object.borrow_safe(|object| {
+        let prototype = object.prototype().map(|prototype| prototype.id());
+
+        (*object.id(), object.is_array(), prototype, object.properties.clone())
+    })
+}
+
+fn function_shape(function: &MutexRef<FunctionType>) -> (Uuid, Vec<Type>, Type, HashMap<String, Type>) {
+    function.borrow_safe(|function| {
+        let arguments = function.arguments().iter().map(|argument| argument.current_type().clone()).collect();
+
+        (*function.id(), arguments, function.return_type(&vec!()), function.properties.clone())
+    })
+}
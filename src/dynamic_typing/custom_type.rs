@@ -1,6 +1,8 @@
-use super::{ FunctionType, ObjectType, CustomType, MutexRef, SafeBorrow };
+use super::{ FunctionType, ObjectType, CustomType, MutexRef, SafeBorrow, Type, Location };
 use uuid::Uuid;
 use std::sync::Arc;
+use serde::{ Serialize, Serializer };
+use super::serialization::CycleSafe;
 
 #[derive(Clone)]
 pub enum CustomTypeRef<'a> {
@@ -8,7 +10,7 @@ pub enum CustomTypeRef<'a> {
     Object(&'a ObjectType),
 }
 
-#[derive(Debug, Serialize, PartialEq, Clone)]
+#[derive(Debug, Clone)]
 pub enum CustomTypeObject {
     Function(MutexRef<FunctionType>),
     Object(MutexRef<ObjectType>),
@@ -26,6 +28,52 @@ impl CustomTypeObject {
             })
         }
     }
+
+    pub fn query_property(&self, property: &str, location: &Location) -> Option<Type> {
+        match self {
+            CustomTypeObject::Function(object) => object.borrow_safe(|object| object.query_property(property, location)),
+            CustomTypeObject::Object(object) => object.borrow_safe(|object| object.query_property(property, location)),
+        }
+    }
+
+    pub fn property_names(&self, location: &Location) -> Vec<String> {
+        match self {
+            CustomTypeObject::Function(object) => object.borrow_safe(|object| object.property_names(location)),
+            CustomTypeObject::Object(object) => object.borrow_safe(|object| object.property_names(location)),
+        }
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.borrow_safe(|object| *object.id())
+    }
+
+    pub fn name(&self) -> String {
+        self.borrow_safe(|object| object.name().to_owned())
+    }
+
+    // functions have no prototype of their own in this model, so the chain ends there
+    pub fn prototype(&self) -> Option<CustomTypeObject> {
+        match self {
+            CustomTypeObject::Function(_) => None,
+            CustomTypeObject::Object(object) => object.borrow_safe(|object| object.prototype().cloned()),
+        }
+    }
+}
+
+// prototypes are compared by identity, so comparing objects never walks into cyclic type graphs
+impl PartialEq for CustomTypeObject {
+    fn eq(&self, other: &CustomTypeObject) -> bool {
+        self.id() == other.id()
+    }
+}
+
+impl Serialize for CustomTypeObject {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        match self {
+            CustomTypeObject::Function(object) => serializer.serialize_newtype_variant("CustomTypeObject", 0, "Function", &CycleSafe(object)),
+            CustomTypeObject::Object(object) => serializer.serialize_newtype_variant("CustomTypeObject", 1, "Object", &CycleSafe(object)),
+        }
+    }
 }
 
 impl<'a> CustomTypeRef<'a> {
@@ -35,6 +83,13 @@ impl<'a> CustomTypeRef<'a> {
             CustomTypeRef::Object(object) => object.id(),
         }
     }
+
+    pub fn name(&self) -> &str {
+        match self {
+            CustomTypeRef::Function(object) => object.name(),
+            CustomTypeRef::Object(object) => object.name(),
+        }
+    }
 }
 
 impl From<&MutexRef<ObjectType>> for CustomTypeObject {
@@ -2,14 +2,28 @@ use std::collections::HashMap;
 use uuid::Uuid;
 
 use super::traits::CustomType;
-use super::{ChangeTrace, Location, TracedChange, TracedTypeMuation, Type, Variable};
+use super::{ChangeTrace, CustomTypeObject, Location, SafeBorrow, TracedChange, TracedTypeMuation, Type, Variable};
+use statics::FUNCTION_PROTOTYPE;
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub enum FunctionKind {
+    Function,
+    Arrow,
+    Method,
+    Class,
+}
 
 #[derive(PartialEq, Debug, Clone, Serialize)]
 pub struct FunctionType {
     id: Uuid,
     name: Option<String>,
+    kind: FunctionKind,
     arguments: Vec<Variable>,
+    return_type: Type,
     pub properties: HashMap<String, Type>,
+    // the [[Prototype]] of the function object itself, a subclass inherits the static
+    // members of its parent class through it
+    prototype: Option<CustomTypeObject>,
     properties_change_trace: ChangeTrace<TracedTypeMuation>,
     invocations: Vec<(Vec<Type>, Location)>,
 }
@@ -21,15 +35,56 @@ impl FunctionType {
         FunctionType {
             id: Uuid::new_v4(),
             name: None,
+            kind: FunctionKind::Function,
             arguments,
+            return_type: Type::Undefined,
             properties: HashMap::new(),
+            prototype: None,
             properties_change_trace: ChangeTrace::new(),
             invocations: vec![],
         }
     }
 
+    pub fn arguments(&self) -> &Vec<Variable> {
+        &self.arguments
+    }
+
+    pub fn set_arguments(&mut self, arguments: Vec<Variable>) {
+        self.arguments = arguments;
+    }
+
     pub fn return_type(&self, _args: &Vec<Type>) -> Type {
-        Type::Undefined
+        self.return_type.clone()
+    }
+
+    pub fn set_return_type(&mut self, return_type: Type) {
+        self.return_type = return_type;
+    }
+
+    pub fn kind(&self) -> &FunctionKind {
+        &self.kind
+    }
+
+    pub fn set_kind(&mut self, kind: FunctionKind) {
+        self.kind = kind;
+    }
+
+    // arrow functions and methods have no [[Construct]] slot and can not be used with `new`
+    pub fn is_constructor(&self) -> bool {
+        match self.kind {
+            FunctionKind::Function => true,
+            FunctionKind::Class => true,
+            FunctionKind::Arrow => false,
+            FunctionKind::Method => false,
+        }
+    }
+
+    pub fn prototype(&self) -> Option<&CustomTypeObject> {
+        self.prototype.as_ref()
+    }
+
+    pub fn set_prototype(&mut self, prototype: CustomTypeObject) {
+        self.prototype = Some(prototype);
     }
 
     pub fn trace_invocation(&mut self, arguments: Vec<Type>, location: Location) {
@@ -37,6 +92,19 @@ impl FunctionType {
     }
 
     pub fn query_property(&self, property: &str, location: &Location) -> Option<Type> {
+        if let Some(property_type) = self.query_own_property(property, location) {
+            return Some(property_type);
+        }
+
+        match self.prototype {
+            Some(ref prototype) => prototype.query_property(property, location),
+
+            // every function inherits call, apply, bind and friends from Function.prototype
+            None => FUNCTION_PROTOTYPE.borrow_safe(|prototype| prototype.query_property(property, location)),
+        }
+    }
+
+    pub fn query_own_property(&self, property: &str, location: &Location) -> Option<Type> {
         let mutation = self.properties_change_trace.find(|change_set| {
             if change_set.loc.start > location.end {
                 return false;
@@ -49,12 +117,32 @@ impl FunctionType {
             }
         });
 
-        match mutation?.attribute {
+        let mutation = match mutation {
+            Some(mutation) => mutation,
+            None => return self.properties.get(property).cloned(),
+        };
+
+        match mutation.attribute {
             TracedTypeMuation::Remove(_) => None,
-            TracedTypeMuation::Add(_) => Some(mutation?.current_type.clone()),
-            TracedTypeMuation::Update(_) => Some(mutation?.current_type.clone()),
+            TracedTypeMuation::Add(_) => Some(mutation.current_type.clone()),
+            TracedTypeMuation::Update(_) => Some(mutation.current_type.clone()),
         }
     }
+
+    pub fn property_names(&self, location: &Location) -> Vec<String> {
+        let mut names: Vec<String> = self.properties.keys().cloned().collect();
+
+        names.extend(self.properties_change_trace.attributes().map(|mutation| match mutation {
+            TracedTypeMuation::Add(name) => name.clone(),
+            TracedTypeMuation::Remove(name) => name.clone(),
+            TracedTypeMuation::Update(name) => name.clone(),
+        }));
+
+        names.retain(|name| self.query_property(name, location).is_some());
+        names.extend(FUNCTION_PROTOTYPE.borrow_safe(|prototype| prototype.property_names(location)));
+
+        names
+    }
 }
 
 impl CustomType for FunctionType {
@@ -1,19 +1,26 @@
+mod assignability;
 mod change_trace;
+mod comparison;
 mod custom_type;
 mod function_type;
 mod object_type;
+mod printer;
 mod scope;
+mod serialization;
 mod traits;
 mod types;
 mod variable;
 
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::ops::Deref;
 use std::sync::{Arc, Mutex};
 
 pub use self::change_trace::*;
+pub use self::comparison::ComparisonMode;
 pub use self::custom_type::*;
-pub use self::function_type::FunctionType;
+pub use self::function_type::{FunctionKind, FunctionType};
 pub use self::object_type::ObjectType;
 pub use self::scope::BindableScope;
 pub use self::scope::{Scope, ScopeRef, Scoped};
@@ -49,9 +56,39 @@ impl<T: Debug + 'static> Debug for CompMutex<T> {
     }
 }
 
-impl<T: PartialEq> PartialEq for CompMutex<T> {
+thread_local! {
+    // pairs of mutexes that are being compared further up the stack, keyed by their addresses
+    static COMPARING: RefCell<HashSet<(usize, usize)>> = RefCell::new(HashSet::new());
+}
+
+impl<T: PartialEq + Clone> PartialEq for CompMutex<T> {
     fn eq(&self, other: &Self) -> bool {
-        *self.try_lock().unwrap() == *other.try_lock().unwrap()
+        // locking the same mutex twice would fail
+        if std::ptr::eq(self, other) {
+            return true;
+        }
+
+        let pair = (self as *const _ as usize, other as *const _ as usize);
+
+        // a pair that is reached again is part of a cycle, recursive shapes are considered equal
+        if !COMPARING.with(|comparing| comparing.borrow_mut().insert(pair)) {
+            return true;
+        }
+
+        // the values are copied out, so no mutex stays locked while the rest of the graph is
+        // compared. A mutex that is locked outside of the comparison can not be read and is
+        // never equal to another one
+        let left = self.try_lock().map(|value| (*value).clone());
+        let right = other.try_lock().map(|value| (*value).clone());
+
+        let is_equal = match (left, right) {
+            (Ok(left), Ok(right)) => left == right,
+            _ => false,
+        };
+
+        COMPARING.with(|comparing| comparing.borrow_mut().remove(&pair));
+
+        is_equal
     }
 }
 
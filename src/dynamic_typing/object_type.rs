@@ -39,7 +39,30 @@ impl ObjectType {
         }
     }
 
+    pub fn is_anonymous(&self) -> bool {
+        self.name.is_none()
+    }
+
+    pub fn prototype(&self) -> Option<&CustomTypeObject> {
+        self.prototype.as_ref()
+    }
+
+    pub fn set_prototype(&mut self, prototype: Option<CustomTypeObject>) {
+        self.prototype = prototype;
+    }
+
     pub fn query_property(&self, property: &str, location: &Location) -> Option<Type> {
+        if let Some(property_type) = self.query_own_property(property, location) {
+            return Some(property_type);
+        }
+
+        match self.prototype {
+            Some(ref prototype) => prototype.query_property(property, location),
+            None => None,
+        }
+    }
+
+    pub fn query_own_property(&self, property: &str, location: &Location) -> Option<Type> {
         let property_type = self.properties.get(property);
 
         let mutation = self.properties_change_trace.find(|change_set| {
@@ -70,6 +93,42 @@ impl ObjectType {
             TracedTypeMuation::Update(_) => Some(mutation.current_type.clone()),
         }
     }
+
+    // the own properties once every traced change has happened
+    pub fn settled_properties(&self) -> HashMap<String, Type> {
+        let mut names: Vec<String> = self.properties.keys().cloned().collect();
+
+        names.extend(self.properties_change_trace.attributes().map(|mutation| match mutation {
+            TracedTypeMuation::Add(name) => name.clone(),
+            TracedTypeMuation::Remove(name) => name.clone(),
+            TracedTypeMuation::Update(name) => name.clone(),
+        }));
+
+        let location = Location::latest();
+
+        names.into_iter()
+            .filter_map(|name| self.query_own_property(&name, &location).map(|property_type| (name, property_type)))
+            .collect()
+    }
+
+    // the names of every property readable at the location, including the ones of the prototype chain
+    pub fn property_names(&self, location: &Location) -> Vec<String> {
+        let mut names: Vec<String> = self.properties.keys().cloned().collect();
+
+        names.extend(self.properties_change_trace.attributes().map(|mutation| match mutation {
+            TracedTypeMuation::Add(name) => name.clone(),
+            TracedTypeMuation::Remove(name) => name.clone(),
+            TracedTypeMuation::Update(name) => name.clone(),
+        }));
+
+        names.retain(|name| self.query_own_property(name, location).is_some());
+
+        if let Some(ref prototype) = self.prototype {
+            names.extend(prototype.property_names(location));
+        }
+
+        names
+    }
 }
 
 impl CustomType for ObjectType {
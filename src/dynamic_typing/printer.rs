@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use super::{ Type, FunctionKind, CustomType, SafeBorrow };
+
+// nested shapes deeper than this are only printed by their name
+const MAX_DEPTH: usize = 3;
+
+impl Type {
+    // renders the shape of objects and the signature of functions, so diagnostics
+    // can show what was expected and what was found, e.g. `{ data: Null | String, test: Number }`
+    pub fn describe(&self) -> String {
+        describe(self, &mut HashSet::new(), 0)
+    }
+}
+
+fn describe(value: &Type, visited: &mut HashSet<Uuid>, depth: usize) -> String {
+    match value {
+        Type::Object(object) => {
+            let (id, name, is_anonymous, mut properties) = object.borrow_safe(|object| {
+                let properties: Vec<(String, Type)> = object.properties.iter()
+                    .map(|(name, property_type)| (name.clone(), property_type.clone()))
+                    .collect();
+
+                (*object.id(), object.name().to_owned(), object.is_anonymous(), properties)
+            });
+
+            // self-referencing shapes are printed by name once they are revisited
+            if depth >= MAX_DEPTH || visited.contains(&id) {
+                return name;
+            }
+
+            if properties.is_empty() {
+                return if is_anonymous { String::from("{}") } else { name };
+            }
+
+            visited.insert(id);
+            properties.sort_by(|left, right| left.0.cmp(&right.0));
+
+            let members: Vec<String> = properties.iter()
+                .map(|(name, property_type)| format!("{}: {}", name, describe(property_type, visited, depth + 1)))
+                .collect();
+
+            visited.remove(&id);
+
+            // named objects keep their name in front of the shape, e.g. `MyObject { test: Number }`
+            if is_anonymous {
+                format!("{{ {} }}", members.join(", "))
+            } else {
+                format!("{} {{ {} }}", name, members.join(", "))
+            }
+        },
+
+        Type::Function(function) => {
+            let (id, name, is_class, arguments, return_type) = function.borrow_safe(|function| {
+                let arguments: Vec<(String, Type)> = function.arguments().iter()
+                    .map(|argument| (argument.name().to_owned(), argument.current_type().clone()))
+                    .collect();
+
+                (*function.id(), function.name().to_owned(), *function.kind() == FunctionKind::Class, arguments, function.return_type(&vec!()))
+            });
+
+            if is_class || depth >= MAX_DEPTH || visited.contains(&id) {
+                return name;
+            }
+
+            visited.insert(id);
+
+            let arguments: Vec<String> = arguments.iter()
+                .map(|(name, argument_type)| format!("{}: {}", name, describe(argument_type, visited, depth + 1)))
+                .collect();
+
+            let signature = format!("({}) => {}", arguments.join(", "), describe(&return_type, visited, depth + 1));
+
+            visited.remove(&id);
+
+            signature
+        },
+
+        Type::Composed { outer, inner } => {
+            let name = outer.borrow_safe(|outer| outer.name().to_owned());
+
+            format!("{}<{}>", name, describe(inner, visited, depth))
+        },
+
+        Type::Mixed(types) => types.iter()
+            .map(|member| describe(member, visited, depth))
+            .collect::<Vec<String>>()
+            .join(" | "),
+
+        _ => value.to_string(),
+    }
+}
@@ -1,11 +1,13 @@
 use uuid::Uuid;
 use std::collections::HashMap;
+use std::sync::Arc;
 use traitcast::cast_box;
 
 use super::variable::Variable;
 use error::ScopeError;
+use jsdoc::Annotations;
 use super::traits::SafeBorrow;
-use super::{ CustomTypeObject, new_mutex_ref, MutexRef, CustomTypeRef, CustomType, FunctionType, ObjectType };
+use super::{ CustomTypeObject, new_mutex_ref, MutexRef, CustomTypeRef, CustomType, FunctionType, ObjectType, Type };
 
 #[derive(Serialize, Debug)]
 pub struct Scope {
@@ -13,11 +15,16 @@ pub struct Scope {
     type_declarations: HashMap<Uuid, CustomTypeObject>,
     variables: HashMap<String, MutexRef<Variable>>,
     parent: Option<MutexRef<Scope>>,
+    this_type: Option<Type>,
+    #[serde(skip)]
+    annotations: Option<Arc<Annotations>>,
+    #[serde(skip)]
+    required_modules: HashMap<String, Type>,
 }
 
 impl Scope {
     pub fn new(name: String, parent: Option<MutexRef<Scope>>) -> Self {
-        Self { name, variables: HashMap::new(), parent, type_declarations: HashMap::new(), }
+        Self { name, variables: HashMap::new(), parent, type_declarations: HashMap::new(), this_type: None, annotations: None, required_modules: HashMap::new() }
     }
 }
 
@@ -28,9 +35,17 @@ pub trait Scoped {
     fn set_name(&mut self, value: String);
     fn locate_own(&self, variable_name: &str) -> Result<MutexRef<Variable>, ScopeError>;
     fn locate_chain(&self, variable_name: &str) -> Result<MutexRef<Variable>, ScopeError>;
+    fn visible_names(&self) -> Vec<String>;
     fn locate(&self, variable_name: &str) -> Result<MutexRef<Variable>, ScopeError>;
+    fn variables(&self) -> Vec<MutexRef<Variable>>;
     fn add(&mut self, variable: Variable);
     fn add_type(&mut self, type_def: CustomTypeObject);
+    fn this_type(&self) -> Type;
+    fn set_this_type(&mut self, this_type: Type);
+    fn annotations(&self) -> Option<Arc<Annotations>>;
+    fn set_annotations(&mut self, annotations: Annotations);
+    fn required_module(&self, specifier: &str) -> Option<Type>;
+    fn add_required_module(&mut self, specifier: String, module_type: Type);
 }
 
 impl Scoped for Scope {
@@ -66,6 +81,17 @@ impl Scoped for Scope {
         }
     }
 
+    // every name locate_chain can find from this scope, the globals of the static root included
+    fn visible_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.variables.keys().cloned().collect();
+
+        if let Some(parent) = &self.parent {
+            names.extend(parent.borrow_safe(|scope| scope.visible_names()));
+        }
+
+        names
+    }
+
     fn locate(&self, variable_name: &str) -> Result<MutexRef<Variable>, ScopeError> {
         let result = self.locate_own(variable_name);
 
@@ -81,6 +107,14 @@ impl Scoped for Scope {
         }
     }
 
+    // the variables declared in this scope, sorted by their name
+    fn variables(&self) -> Vec<MutexRef<Variable>> {
+        let mut names: Vec<&String> = self.variables.keys().collect();
+
+        names.sort();
+        names.into_iter().map(|name| self.variables[name].clone()).collect()
+    }
+
     fn add(&mut self, variable: Variable) {
         self.variables.insert(variable.name().to_owned(), new_mutex_ref(variable));
     }
@@ -94,6 +128,54 @@ impl Scoped for Scope {
 
         self.type_declarations.insert(type_id, type_def);
     }
+
+    fn this_type(&self) -> Type {
+        if let Some(ref this_type) = self.this_type {
+            return this_type.clone();
+        }
+
+        // modules are always strict, so there is no global object bound to this
+        match &self.parent {
+            Some(parent) => parent.borrow_safe(|scope| scope.this_type()),
+            None => Type::Undefined,
+        }
+    }
+
+    fn set_this_type(&mut self, this_type: Type) {
+        self.this_type = Some(this_type);
+    }
+
+    // doc comments belong to the module they were written in, nested scopes share them
+    fn annotations(&self) -> Option<Arc<Annotations>> {
+        if let Some(ref annotations) = self.annotations {
+            return Some(annotations.clone());
+        }
+
+        match &self.parent {
+            Some(parent) => parent.borrow_safe(|scope| scope.annotations()),
+            None => None,
+        }
+    }
+
+    fn set_annotations(&mut self, annotations: Annotations) {
+        self.annotations = Some(Arc::new(annotations));
+    }
+
+    // what `require(specifier)` evaluates to in this scope and every scope below it
+    fn required_module(&self, specifier: &str) -> Option<Type> {
+        if let Some(module_type) = self.required_modules.get(specifier) {
+            return Some(module_type.clone());
+        }
+
+        match &self.parent {
+            Some(parent) => parent.borrow_safe(|scope| scope.required_module(specifier)),
+            None => None,
+        }
+    }
+
+    fn add_required_module(&mut self, specifier: String, module_type: Type) {
+        self.required_modules.insert(specifier, module_type);
+    }
 }
 
 impl Scoped for ScopeRef {
@@ -113,10 +195,18 @@ impl Scoped for ScopeRef {
         self.borrow_safe(|scope| scope.locate_chain(variable_name))
     }
 
+    fn visible_names(&self) -> Vec<String> {
+        self.borrow_safe(|scope| scope.visible_names())
+    }
+
     fn locate(&self, variable_name: &str) -> Result<MutexRef<Variable>, ScopeError> {
         self.borrow_safe(|scope| scope.locate(variable_name))
     }
 
+    fn variables(&self) -> Vec<MutexRef<Variable>> {
+        self.borrow_safe(|scope| scope.variables())
+    }
+
     fn add(&mut self, variable: Variable) {
         self.borrow_mut_safe(|scope| scope.add(variable))
     }
@@ -124,6 +214,30 @@ impl Scoped for ScopeRef {
     fn add_type(&mut self, type_def: CustomTypeObject) {
         self.borrow_mut_safe(|scope| scope.add_type(type_def))
     }
+
+    fn this_type(&self) -> Type {
+        self.borrow_safe(|scope| scope.this_type())
+    }
+
+    fn set_this_type(&mut self, this_type: Type) {
+        self.borrow_mut_safe(|scope| scope.set_this_type(this_type))
+    }
+
+    fn annotations(&self) -> Option<Arc<Annotations>> {
+        self.borrow_safe(|scope| scope.annotations())
+    }
+
+    fn set_annotations(&mut self, annotations: Annotations) {
+        self.borrow_mut_safe(|scope| scope.set_annotations(annotations))
+    }
+
+    fn required_module(&self, specifier: &str) -> Option<Type> {
+        self.borrow_safe(|scope| scope.required_module(specifier))
+    }
+
+    fn add_required_module(&mut self, specifier: String, module_type: Type) {
+        self.borrow_mut_safe(|scope| scope.add_required_module(specifier, module_type))
+    }
 }
 
 impl ToString for Scope {
@@ -0,0 +1,37 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use serde::{ Serialize, Serializer };
+use uuid::Uuid;
+
+use super::{ CustomType, MutexRef, SafeBorrow };
+
+thread_local! {
+    // ids of the custom types that are currently being serialized further up the stack, keyed
+    // by their address, because their mutex is locked until they are done
+    static SERIALIZING: RefCell<HashMap<usize, Uuid>> = RefCell::new(HashMap::new());
+}
+
+// serializes a custom type in full the first time it is reached, and only as a reference to
+// its id while it is already being serialized, so cyclic type graphs terminate
+pub struct CycleSafe<'a, T: 'a>(pub &'a MutexRef<T>);
+
+impl<'a, T: CustomType + Serialize> Serialize for CycleSafe<'a, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let address = &**self.0 as *const _ as usize;
+        let in_progress = SERIALIZING.with(|serializing| serializing.borrow().get(&address).cloned());
+
+        if let Some(id) = in_progress {
+            return serializer.serialize_newtype_struct("Reference", &id);
+        }
+
+        let result = self.0.borrow_safe(|custom_type| {
+            SERIALIZING.with(|serializing| serializing.borrow_mut().insert(address, *custom_type.id()));
+
+            custom_type.serialize(serializer)
+        });
+
+        SERIALIZING.with(|serializing| serializing.borrow_mut().remove(&address));
+
+        result
+    }
+}
@@ -1,15 +1,16 @@
 use std::collections::HashMap;
 
-use super::{ ObjectType, FunctionType, SafeBorrow, MutexRef, new_mutex_ref, CustomTypeObject, Location };
+use super::{ ObjectType, FunctionType, SafeBorrow, MutexRef, new_mutex_ref, CustomTypeObject, Location, ComparisonMode };
 use super::traits::CustomType;
-use statics::{ OBJECT_PROTOTYPE };
+use statics::{ OBJECT_PROTOTYPE, STRING_PROTOTYPE, NUMBER_PROTOTYPE, BOOLEAN_PROTOTYPE, REGEXP_PROTOTYPE };
 use std::sync::{ Arc };
 use ratel::{ ast as Ast };
 
-//use serde::{ Serialize, Serializer };
-//use serde::ser::{ SerializeStructVariant };
+use serde::{ Serialize, Serializer };
+use serde::ser::{ SerializeStructVariant };
+use super::serialization::CycleSafe;
 
-#[derive(PartialEq, Debug, Clone, Serialize)]
+#[derive(Debug, Clone)]
 pub enum Type {
     Number,
     String,
@@ -24,6 +25,54 @@ pub enum Type {
 }
 
 impl Type {
+    // the canonical form of a union: nested unions are flattened, duplicates removed,
+    // the members sorted and a union of a single type is just that type
+    pub fn union(types: Vec<Type>) -> Type {
+        let mut members: Vec<Type> = vec!();
+
+        for member in types {
+            let flattened = match member {
+                Type::Mixed(nested) => match Type::union(nested) {
+                    Type::Mixed(nested) => nested,
+                    single => vec!(single),
+                },
+                _ => vec!(member),
+            };
+
+            // object literals of the same shape are one member of the union
+            for member in flattened {
+                if !members.iter().any(|existing| existing.equals(&member, ComparisonMode::Structural)) {
+                    members.push(member);
+                }
+            }
+        }
+
+        members.sort_by_key(|member| member.sort_key());
+
+        match members.len() {
+            1 => members.remove(0),
+            _ => Type::Mixed(members),
+        }
+    }
+
+    // custom types are ordered by their name, ids are random and would order them differently in
+    // every run. Types with the same name, or that are borrowed further up the stack, keep the
+    // order they were added in
+    fn sort_key(&self) -> (u8, String) {
+        match self {
+            Type::Number => (0, String::new()),
+            Type::String => (1, String::new()),
+            Type::Boolean => (2, String::new()),
+            Type::RegExp => (3, String::new()),
+            Type::Object(object) => (4, object.try_lock().map(|object| object.name().to_owned()).unwrap_or_default()),
+            Type::Function(object) => (5, object.try_lock().map(|object| object.name().to_owned()).unwrap_or_default()),
+            Type::Composed { outer, .. } => (6, outer.try_lock().map(|outer| outer.name().to_owned()).unwrap_or_default()),
+            Type::Mixed(_) => (7, String::new()),
+            Type::Null => (8, String::new()),
+            Type::Undefined => (9, String::new()),
+        }
+    }
+
     pub fn unwrap(&self) -> Type {
         match self {
             Type::Composed { inner, .. } => {
@@ -41,12 +90,23 @@ impl Type {
             Type::Function(object) => object.borrow_safe(|object| {
                 closure(&object.properties)
             }),
-            _ => OBJECT_PROTOTYPE.borrow_safe(|object| {
+            _ => self.primitive_prototype().unwrap_or_else(|| OBJECT_PROTOTYPE.clone()).borrow_safe(|object| {
                 closure(&object.properties)
             }),
         }
     }
 
+    // primitive values are boxed into an instance of their wrapper when a property is accessed
+    pub fn primitive_prototype(&self) -> Option<MutexRef<ObjectType>> {
+        match self {
+            Type::String => Some(STRING_PROTOTYPE.clone()),
+            Type::Number => Some(NUMBER_PROTOTYPE.clone()),
+            Type::Boolean => Some(BOOLEAN_PROTOTYPE.clone()),
+            Type::RegExp => Some(REGEXP_PROTOTYPE.clone()),
+            _ => None,
+        }
+    }
+
     pub fn properties_mut<Func: Fn(&mut HashMap<String, Type>)>(&mut self, closure: Func) {
         match self {
             Type::Object(object) => object.borrow_mut_safe(|object| {
@@ -71,41 +131,57 @@ impl Type {
         match self {
             Type::Object(data) => data.borrow_safe(|data| data.query_property(property, location)),
             Type::Function(data) => data.borrow_safe(|data| data.query_property(property, location)),
-            _ => None,
+            Type::Composed { outer, .. } => outer.borrow_safe(|outer| outer.query_property(property, location)),
+            _ => match self.primitive_prototype() {
+                Some(prototype) => prototype.borrow_safe(|prototype| prototype.query_property(property, location)),
+                None => None,
+            },
+        }
+    }
+
+    // the properties a member access on the type can read at the location
+    pub fn property_names(&self, location: &Location) -> Vec<String> {
+        match self {
+            Type::Object(data) => data.borrow_safe(|data| data.property_names(location)),
+            Type::Function(data) => data.borrow_safe(|data| data.property_names(location)),
+            Type::Composed { outer, .. } => outer.borrow_safe(|outer| outer.property_names(location)),
+            _ => match self.primitive_prototype() {
+                Some(prototype) => prototype.borrow_safe(|prototype| prototype.property_names(location)),
+                None => vec!(),
+            },
         }
     }
 }
 
-// impl Serialize for Type {
-//     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
-//         match self {
-//             Type::Number => serializer.serialize_unit_variant("Type", 0, "Number"),
-//             Type::String => serializer.serialize_unit_variant("Type", 1, "String"),
-//             Type::Boolean => serializer.serialize_unit_variant("Type", 2, "Boolean"),
-//             Type::RegExp => serializer.serialize_unit_variant("Type", 3, "RegExp"),
-//             Type::Object(object_data) => {
-//                 let id = object_data.id();
-//
-//                 serializer.serialize_newtype_variant("Type", 4, "Object", id)
-//             },
-//             Type::Function(object_data) => {
-//                 let id = object_data.id();
-//
-//                 serializer.serialize_newtype_variant("Type", 5, "Function", id)
-//             },
-//             Type::Undefined => serializer.serialize_unit_variant("Type", 6, "Undefined"),
-//             Type::Mixed(value) => serializer.serialize_newtype_variant("Type", 7, "Mixed", value),
-//             Type::Composed { outer, inner } => {
-//                 let mut state = serializer.serialize_struct_variant("Type", 8, "Composed", 2)?;
-//
-//                 state.serialize_field("outer", outer.id())?;
-//                 state.serialize_field("inner", inner)?;
-//                 state.end()
-//             }
-//         }
-//     }
-// }
+// custom types are compared by their identity, unions by their members regardless of order
+impl PartialEq for Type {
+    fn eq(&self, other: &Type) -> bool {
+        self.equals(other, ComparisonMode::Identity)
+    }
+}
+
+impl Serialize for Type {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        match self {
+            Type::Number => serializer.serialize_unit_variant("Type", 0, "Number"),
+            Type::String => serializer.serialize_unit_variant("Type", 1, "String"),
+            Type::Boolean => serializer.serialize_unit_variant("Type", 2, "Boolean"),
+            Type::RegExp => serializer.serialize_unit_variant("Type", 3, "RegExp"),
+            Type::Object(object_data) => serializer.serialize_newtype_variant("Type", 4, "Object", &CycleSafe(object_data)),
+            Type::Function(object_data) => serializer.serialize_newtype_variant("Type", 5, "Function", &CycleSafe(object_data)),
+            Type::Undefined => serializer.serialize_unit_variant("Type", 6, "Undefined"),
+            Type::Null => serializer.serialize_unit_variant("Type", 7, "Null"),
+            Type::Mixed(value) => serializer.serialize_newtype_variant("Type", 8, "Mixed", value),
+            Type::Composed { outer, inner } => {
+                let mut state = serializer.serialize_struct_variant("Type", 9, "Composed", 2)?;
 
+                state.serialize_field("outer", &CycleSafe(outer))?;
+                state.serialize_field("inner", inner)?;
+                state.end()
+            }
+        }
+    }
+}
 
 impl ToString for Type {
     fn to_string(&self) -> String {
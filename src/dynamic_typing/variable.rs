@@ -6,6 +6,8 @@ use ratel::{ ast as Ast };
 pub struct Variable {
     name: String,
     current_type: Type,
+    // the type the variable was annotated with, assignments have to stay within it
+    declared_type: Option<Type>,
     change_trace: ChangeTrace<TracedTypeChange>,
     kind: VariableKind,
 }
@@ -15,14 +17,41 @@ impl Variable {
         &self.current_type
     }
 
+    pub fn declared_type(&self) -> Option<&Type> {
+        self.declared_type.as_ref()
+    }
+
+    // the inferred type stays as it is, values are checked against the declared type when
+    // they are assigned
+    pub fn set_declared_type(&mut self, declared_type: Type) {
+        self.declared_type = Some(declared_type);
+    }
+
+    // a variable that was declared without a value and was never assigned takes the type of
+    // its first assignment, if the policy allows it
+    pub fn widen(&mut self, assigned_type: &Type, policy: WideningPolicy) {
+        if policy == WideningPolicy::Never || self.declared_type.is_some() || !self.change_trace.is_empty() {
+            return;
+        }
+
+        if self.current_type == Type::Undefined {
+            self.current_type = assigned_type.clone();
+        }
+    }
+
     pub fn name(&self)  -> &str {
         &self.name
     }
 
+    pub fn kind(&self) -> &VariableKind {
+        &self.kind
+    }
+
     pub fn new(name: String, current_type: Type, kind: VariableKind) -> Self {
         Self {
             name,
             current_type,
+            declared_type: None,
             kind,
             change_trace: ChangeTrace::new(),
         }
@@ -35,6 +64,21 @@ impl TracedChange<TracedTypeChange, Type, Location> for Variable {
     }
 }
 
+#[derive(PartialEq, Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WideningPolicy {
+    // `let a; a = 1;` makes `a` a number, later assignments of other types change its inferred type
+    FirstAssignment,
+    // variables declared without a value stay unknown and accept any assignment
+    Never,
+}
+
+impl Default for WideningPolicy {
+    fn default() -> Self {
+        WideningPolicy::FirstAssignment
+    }
+}
+
 #[derive(PartialEq, Debug, Clone, Serialize)]
 pub enum VariableKind {
     Const,
@@ -2,9 +2,15 @@ use failure::*;
 use dynamic_typing::{ Location };
 use std::sync::Arc;
 use std::collections::hash_set::HashSet;
+use std::collections::HashMap;
+use config::Severity;
+use suggestions::Suggestions;
 
 pub type ErrorVec = HashSet<Arc<ValidationError>>;
 
+// reported diagnostics and the severity configured for their rule
+pub type Diagnostics = HashMap<Arc<ValidationError>, Severity>;
+
 #[derive(Debug, Fail)]
 pub enum ScopeError {
     #[fail(display = "variable \"{}\" is undefinded in current scope \"{}\"!", variable_name, scope_name)]
@@ -35,6 +41,11 @@ pub enum TypeError {
         type_name: String,
     },
 
+    #[fail(display = "\"{}\" is not a constructor!", type_name)]
+    NotConstructor {
+        type_name: String,
+    },
+
     #[fail(display = "Unable to modify property \"{}\" of trimitive type \"{}\"!", property, type_name)]
     PrimitivePropertyWrite {
         type_name: String,
@@ -42,17 +53,33 @@ pub enum TypeError {
     },
 }
 
+#[derive(Debug, Fail)]
+pub enum DeclarationError {
+    #[fail(display = "expected {} but found \"{}\" at offset {}", expected, found, offset)]
+    UnexpectedToken {
+        expected: String,
+        found: String,
+        offset: u32,
+    },
+
+    #[fail(display = "unterminated string starting at offset {}", offset)]
+    UnterminatedString {
+        offset: u32,
+    },
+}
+
 #[derive(Debug, Fail, Eq, PartialEq, Hash)]
 pub enum ValidationError {
 
-    #[fail(display = "\"{}\" has no property \"{}\"", object, property)]
+    #[fail(display = "\"{}\" has no property \"{}\"{}", object, property, suggestions)]
     UnknownProperty {
         object: String,
         property: String,
+        suggestions: Suggestions,
         location: Location,
     },
 
-    #[fail(display = "\"{}\" has type \"{}\" but type \"{}\" was assigned", target, own_type, their_type)]
+    #[fail(display = "\"{}\" was inferred as \"{}\" but type \"{}\" was assigned", target, own_type, their_type)]
     AssignTypeMissmatch {
         target: String,
         location: Location,
@@ -67,10 +94,12 @@ pub enum ValidationError {
         location: Location,
     },
 
-    #[fail(display = "variable \"{}\" is undefinded in current scope \"{}\"!", variable_name, scope_name)]
+    #[fail(display = "variable \"{}\" is undefinded in current scope \"{}\"{}", variable_name, scope_name, suggestions)]
     UndefinedVariable {
         variable_name: String,
         scope_name: String,
+        suggestions: Suggestions,
+        location: Location,
     },
 
     #[fail(display = "\"{}\" must be of type \"{}\" but is \"{}\" here", expression, current_type, expected_type)]
@@ -84,6 +113,63 @@ pub enum ValidationError {
     #[fail(display = "\"{}\" is a useless comparison and should be removed", expression)]
     NonsensicalComparison {
         expression: String,
+        // the value the comparison always has, if it only compares literals
+        outcome: Option<bool>,
+        location: Location,
+    },
+
+    #[fail(display = "\"{}\" of type \"{}\" is not a constructor and can not be used with new", expression, type_name)]
+    NotConstructor {
+        expression: String,
+        type_name: String,
+        location: Location,
+    },
+
+    #[fail(display = "\"{}\" expects an argument of type \"{}\" but \"{}\" of type \"{}\" was passed", function, expected_type, argument, their_type)]
+    ArgumentTypeMissmatch {
+        function: String,
+        argument: String,
+        expected_type: String,
+        their_type: String,
+        location: Location,
+    },
+
+    #[fail(display = "\"{}\" is declared as \"{}\" but type \"{}\" was assigned", variable, declared_type, inferred_type)]
+    DeclaredTypeMissmatch {
+        variable: String,
+        declared_type: String,
+        inferred_type: String,
+        location: Location,
+    },
+
+    #[fail(display = "function is documented to return \"{}\" but returns \"{}\"", declared_type, inferred_type)]
+    ReturnTypeMissmatch {
+        declared_type: String,
+        inferred_type: String,
+        location: Location,
+    },
+
+    #[fail(display = "module \"{}\" could not be resolved", specifier)]
+    UnresolvedModule {
+        specifier: String,
+        location: Location,
+    },
+
+    #[fail(display = "module \"{}\" imports this module, its exports are unknown here", specifier)]
+    CyclicImport {
+        specifier: String,
+        location: Location,
+    },
+
+    #[fail(display = "suppression of \"{}\" is unused, nothing is reported there", rule)]
+    UnusedSuppression {
+        rule: String,
+        location: Location,
+    },
+
+    #[fail(display = "\"{}\" converts its operands before comparing them, use \"{}=\" instead", operator, operator)]
+    LooseEquality {
+        operator: String,
         location: Location,
     },
 }
@@ -92,20 +178,80 @@ impl ValidationError {
     pub fn location(&self) -> &Location {
         match self {
             ValidationError::UnknownProperty { location, .. } => &location,
-            ValidationError::UndefinedVariable { .. } => &Location { column: 0, end: 0, line: 0, start: 0, },
+            ValidationError::UndefinedVariable { location, .. } => &location,
             ValidationError::AssignTypeMissmatch { location, .. } => &location,
             ValidationError::CompareTypeMissmatch { location, .. } => &location,
             ValidationError::InvalidType { location, .. } => &location,
             ValidationError::NonsensicalComparison { location, .. } => &location,
+            ValidationError::NotConstructor { location, .. } => &location,
+            ValidationError::ArgumentTypeMissmatch { location, .. } => &location,
+            ValidationError::DeclaredTypeMissmatch { location, .. } => &location,
+            ValidationError::ReturnTypeMissmatch { location, .. } => &location,
+            ValidationError::UnresolvedModule { location, .. } => &location,
+            ValidationError::CyclicImport { location, .. } => &location,
+            ValidationError::UnusedSuppression { location, .. } => &location,
+            ValidationError::LooseEquality { location, .. } => &location,
+        }
+    }
+
+    // the names the diagnostic proposes instead of a misspelled one
+    pub fn suggestions(&self) -> &[String] {
+        match self {
+            ValidationError::UnknownProperty { suggestions, .. } => suggestions.names(),
+            ValidationError::UndefinedVariable { suggestions, .. } => suggestions.names(),
+            _ => &[],
+        }
+    }
+
+    // stable codes, new diagnostics are added at the end
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValidationError::UnknownProperty { .. } => "R001",
+            ValidationError::UndefinedVariable { .. } => "R002",
+            ValidationError::AssignTypeMissmatch { .. } => "R003",
+            ValidationError::CompareTypeMissmatch { .. } => "R004",
+            ValidationError::InvalidType { .. } => "R005",
+            ValidationError::NonsensicalComparison { .. } => "R006",
+            ValidationError::NotConstructor { .. } => "R007",
+            ValidationError::ArgumentTypeMissmatch { .. } => "R008",
+            ValidationError::DeclaredTypeMissmatch { .. } => "R009",
+            ValidationError::ReturnTypeMissmatch { .. } => "R010",
+            ValidationError::UnresolvedModule { .. } => "R011",
+            ValidationError::CyclicImport { .. } => "R012",
+            ValidationError::UnusedSuppression { .. } => "R013",
+            ValidationError::LooseEquality { .. } => "R014",
+        }
+    }
+
+    // the name the severity of the diagnostic can also be configured by
+    pub fn name(&self) -> &'static str {
+        match self {
+            ValidationError::UnknownProperty { .. } => "UnknownProperty",
+            ValidationError::UndefinedVariable { .. } => "UndefinedVariable",
+            ValidationError::AssignTypeMissmatch { .. } => "AssignTypeMissmatch",
+            ValidationError::CompareTypeMissmatch { .. } => "CompareTypeMissmatch",
+            ValidationError::InvalidType { .. } => "InvalidType",
+            ValidationError::NonsensicalComparison { .. } => "NonsensicalComparison",
+            ValidationError::NotConstructor { .. } => "NotConstructor",
+            ValidationError::ArgumentTypeMissmatch { .. } => "ArgumentTypeMissmatch",
+            ValidationError::DeclaredTypeMissmatch { .. } => "DeclaredTypeMissmatch",
+            ValidationError::ReturnTypeMissmatch { .. } => "ReturnTypeMissmatch",
+            ValidationError::UnresolvedModule { .. } => "UnresolvedModule",
+            ValidationError::CyclicImport { .. } => "CyclicImport",
+            ValidationError::UnusedSuppression { .. } => "UnusedSuppression",
+            ValidationError::LooseEquality { .. } => "LooseEquality",
         }
     }
 }
 
-impl From<ScopeError> for ValidationError {
-    fn from(error: ScopeError) -> Self {
+impl ValidationError {
+    // scope errors do not know where the variable was used, the identifier it was read by does
+    pub fn undefined_variable(error: ScopeError, candidates: Vec<String>, location: Location) -> Self {
         match error {
             ScopeError::UndefinedVariable { variable_name, scope_name } => {
-                ValidationError::UndefinedVariable { variable_name, scope_name }
+                let suggestions = Suggestions::closest(&variable_name, candidates);
+
+                ValidationError::UndefinedVariable { variable_name, scope_name, suggestions, location }
             }
         }
     }
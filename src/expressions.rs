@@ -1,14 +1,16 @@
 use ratel::ast as Ast;
 use dynamic_typing::{
-    Type, SafeBorrow, CustomTypeObject, FunctionType, ObjectType,
-    new_mutex_ref, CustomType, Scoped, ScopeRef
+    Type, SafeBorrow, CustomType, Scoped, ScopeRef
 };
 use failure::*;
 use statics::ARRAY_PROTOTYPE;
-use std::collections::HashMap;
-use error::TypeError;
 use literals::literal_to_string;
 use objects::{ type_from_properties, determine_member_type };
+use classes::{ type_from_class, instance_of };
+use functions::{ function_type_from, arrow_type_from, literal_start, arrow_body_start };
+use arrays::{ array_call_type, element_type };
+use error::TypeError;
+use modules::required_module_type;
 
 pub fn determine_expression_type(expression: &Ast::Expression, scope: &ScopeRef) -> Result<Type, Error> {
     let var_type: Type = match expression {
@@ -20,15 +22,13 @@ pub fn determine_expression_type(expression: &Ast::Expression, scope: &ScopeRef)
 
             variable.borrow_safe(|variable| variable.current_type().clone())
         },
-        Ast::Expression::This { .. } => {
-            Type::Undefined
-        },
+        Ast::Expression::This { .. } => scope.this_type(),
         Ast::Expression::Array(array_expression) => {
             let mixed: Result<Vec<Type>, Error> = array_expression.body.iter()
                 .map(|element| determine_expression_type(&element.item, scope))
                 .collect();
 
-            Type::Composed { outer: (*ARRAY_PROTOTYPE).clone(), inner: Box::new(Type::Mixed(mixed?))}
+            Type::Composed { outer: (*ARRAY_PROTOTYPE).clone(), inner: Box::new(Type::union(mixed?))}
         },
 
         Ast::Expression::Object(object_expression) => {
@@ -37,8 +37,14 @@ pub fn determine_expression_type(expression: &Ast::Expression, scope: &ScopeRef)
             type_from_properties(&properties, scope)?
         },
 
-        Ast::Expression::Function { .. } => {
-            panic!("function expressions are not implemented!");
+        Ast::Expression::Function(function) => {
+            let Ast::OptionalName(name) = function.name;
+            let name = name.map(|name| name.item.to_string());
+
+            // plain functions are strict mode code, so they are not bound to a this value
+            let start = literal_start(&function.params, function.body.start);
+
+            Type::from(function_type_from(name, &function.params, &function.body, Some(Type::Undefined), start, scope)?)
         },
 
         // no properly implemented yet, this has to determine the type of the operation
@@ -56,15 +62,19 @@ pub fn determine_expression_type(expression: &Ast::Expression, scope: &ScopeRef)
             let left_type = determine_expression_type(&alternate.item, scope)?;
             let right_type = determine_expression_type(&consequent.item, scope)?;
 
-            if left_type == right_type {
-                left_type
-            } else {
-                Type::Mixed(vec!(left_type, right_type))
-            }
+            Type::union(vec!(left_type, right_type))
         },
 
         //this is not right because we actually need the return type not the function type
         Ast::Expression::Call(call_expression) => {
+            if let Some(return_type) = array_call_type(call_expression, scope) {
+                return Ok(return_type);
+            }
+
+            if let Some(module_type) = required_module_type(call_expression, scope) {
+                return Ok(module_type);
+            }
+
             let function_type = determine_expression_type(&call_expression.callee.item, scope)?;
             let argument_types: Vec<Type> = call_expression.arguments.iter().map(|expression_node| {
                 determine_expression_type(&**expression_node, scope)
@@ -72,11 +82,17 @@ pub fn determine_expression_type(expression: &Ast::Expression, scope: &ScopeRef)
 
             match function_type {
                 Type::Function(function_type) => function_type.borrow_safe(|function_type| function_type.return_type(&argument_types)),
-                _ => panic!("unable to call {}, it's not a function!", expression_to_string(&call_expression.callee.item))
+
+                // nothing is known about the callee, so nothing is known about its result either
+                Type::Undefined => Type::Undefined,
+
+                _ => return Err(TypeError::NotFunction { type_name: expression_to_string(&call_expression.callee.item) }.into()),
             }
         },
-        Ast::Expression::Arrow(_) => {
-            Type::Function(new_mutex_ref(FunctionType::new(vec!())))
+        Ast::Expression::Arrow(arrow_expression) => {
+            let start = literal_start(&arrow_expression.params, arrow_body_start(&arrow_expression.body));
+
+            Type::from(arrow_type_from(&arrow_expression.params, &arrow_expression.body, start, scope)?)
         },
 
         Ast::Expression::Sequence(list) => {
@@ -89,6 +105,14 @@ pub fn determine_expression_type(expression: &Ast::Expression, scope: &ScopeRef)
         Ast::Expression::Template(_) => Type::String,
         Ast::Expression::ComputedMember(expression) => {
             let Ast::expression::ComputedMemberExpression { object, property } = expression;
+
+            // numeric indices into an array yield its elements
+            if let Some(element_type) = element_type(&determine_expression_type(&object.item, scope)?) {
+                if let Type::Number = determine_expression_type(&property.item, scope)? {
+                    return Ok(element_type);
+                }
+            }
+
             let expression_string =  expression_to_string(&property.item);
             let location = Ast::Loc::new(property.start, property.end, &expression_string[..]);
             let property_node = Ast::Node::new(&location);
@@ -103,6 +127,10 @@ pub fn determine_expression_type(expression: &Ast::Expression, scope: &ScopeRef)
         Ast::Expression::MetaProperty(_expression) => Type::Undefined,
 
         Ast::Expression::Prefix(expression) => {
+            if let Ast::OperatorKind::New = expression.operator {
+                return new_expression_type(&expression.operand, scope);
+            }
+
             return determine_expression_type(&expression.operand, scope);
         }
 
@@ -126,57 +154,20 @@ pub fn determine_expression_type(expression: &Ast::Expression, scope: &ScopeRef)
             }
         },
 
-        Ast::Expression::Class(expression) => {
-            let mut constructor = FunctionType::new(vec!());
-            let mut prototype_properties = HashMap::new();
-
-            let Ast::OptionalName(name) = expression.name;
-
-            let name = match name {
-                Some(name) => Some(name.to_string()),
-                None => None,
-            };
-
-
-            let parent_prototype = match expression.extends {
-                Some(expression) => Some(determine_expression_type(&expression.item, scope)?),
-                None => None,
-            };
-
-            let parent_prototype: Option<CustomTypeObject> = match parent_prototype {
-                Some(proto) => match proto {
-                    Type::Object(ref object_type) => Some(CustomTypeObject::from(object_type)),
-                    Type::Function(ref func_type) => Some(CustomTypeObject::from(func_type)),
-                    _ => return Err(TypeError::IncompatiblePrototype { prototype: proto.to_string() }.into())
-                },
-
-                None => None,
-            };
-
-            if let Some(ref name) = name {
-                constructor.assign_name(name.to_string());
-            }
-
-            let mut constructor_type = Type::from(constructor);
-
-            prototype_properties.insert(String::from("constructor"), constructor_type.clone());
-
-            let prototype_name = match name {
-                Some(name) => Some(format!("{}Prototype", name)),
-                None => None
-            };
-
-            let prototype = new_mutex_ref(ObjectType::new(prototype_name, prototype_properties, parent_prototype));
+        Ast::Expression::Class(expression) => type_from_class(expression, scope)?,
+    };
 
-            constructor_type.properties_mut(|properties| {
-                properties.insert("prototype".to_owned(), Type::from(&prototype));
-            });
+    Ok(var_type)
+}
 
-            constructor_type
-        }
+pub fn new_expression_type(operand: &Ast::Expression, scope: &ScopeRef) -> Result<Type, Error> {
+    // `new Foo(...)` is parsed as a prefixed call, while `new Foo` has no call at all
+    let constructor_type = match operand {
+        Ast::Expression::Call(call_expression) => determine_expression_type(&call_expression.callee.item, scope)?,
+        _ => determine_expression_type(operand, scope)?,
     };
 
-    Ok(var_type)
+    instance_of(&constructor_type)
 }
 
 pub fn expression_to_string(expression: &Ast::Expression) -> String {
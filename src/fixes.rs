@@ -0,0 +1,313 @@
+use std::fs::File;
+use std::io::prelude::*;
+use std::sync::Arc;
+use failure::Error;
+
+use dynamic_typing::Location;
+use error::ValidationError;
+use modules::ModuleRecord;
+
+// replaces the source between two offsets
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Edit {
+    pub start: u32,
+    pub end: u32,
+    pub replacement: String,
+}
+
+// a change that resolves a diagnostic and can be applied without asking
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Fix {
+    pub description: String,
+    pub edits: Vec<Edit>,
+}
+
+impl Fix {
+    fn single(description: String, start: u32, end: u32, replacement: String) -> Self {
+        Fix { description, edits: vec!(Edit { start, end, replacement }) }
+    }
+
+    fn overlaps(&self, other: &Fix) -> bool {
+        self.edits.iter().any(|edit| other.edits.iter().any(|other| edit.start < other.end && other.start < edit.end))
+    }
+}
+
+// the fix of a diagnostic in the given source, if the source still looks like it expects
+pub fn fix_for(error: &ValidationError, source: &str) -> Option<Fix> {
+    match error {
+        ValidationError::UnknownProperty { property, suggestions, location, .. } => {
+            let suggestion = suggestions.best(property)?;
+            let (start, end) = find_word(source, location, property)?;
+
+            // a missing export was imported without a local name, the binding keeps its name
+            let replacement = if end < location.end && !followed_by_as(&source[end as usize..]) && is_import(source, location) {
+                format!("{} as {}", suggestion, property)
+            } else {
+                suggestion.to_owned()
+            };
+
+            Some(Fix::single(format!("replace \"{}\" with \"{}\"", property, suggestion), start, end, replacement))
+        },
+
+        ValidationError::LooseEquality { operator, location } => {
+            let start = operator_offset(source, location, operator)?;
+
+            Some(Fix::single(format!("use \"{}=\"", operator), start, start + 2, format!("{}=", operator)))
+        },
+
+        // comparisons that depend on more than literals are left alone
+        ValidationError::NonsensicalComparison { outcome: Some(outcome), location, .. } => remove_condition(source, location, *outcome),
+
+        _ => None,
+    }
+}
+
+// applies the fixes in the order of the source, a fix that overlaps an earlier one is skipped.
+// Returns the new source and the indices of the applied fixes
+pub fn apply_fixes(source: &str, fixes: &[Fix]) -> (String, Vec<usize>) {
+    let mut order: Vec<usize> = (0..fixes.len()).collect();
+
+    order.sort_by_key(|index| fixes[*index].edits.iter().map(|edit| edit.start).min());
+
+    let mut applied: Vec<usize> = vec!();
+
+    for index in order {
+        if applied.iter().all(|other| !fixes[index].overlaps(&fixes[*other])) {
+            applied.push(index);
+        }
+    }
+
+    let mut edits: Vec<&Edit> = applied.iter().flat_map(|index| fixes[*index].edits.iter()).collect();
+    let mut output = source.to_owned();
+
+    // from the end of the source, so the offsets of the remaining edits stay valid
+    edits.sort_by_key(|edit| edit.start);
+
+    for edit in edits.into_iter().rev() {
+        output.replace_range(edit.start as usize..edit.end as usize, &edit.replacement);
+    }
+
+    applied.sort();
+
+    (output, applied)
+}
+
+// writes the fixable diagnostics of the module back to its file, the record holds the new
+// source and the diagnostics that are left in front of the first edit. The locations of the
+// ones after it moved, they are dropped until the next run finds them again
+pub fn fix_module(module: &ModuleRecord) -> Result<(ModuleRecord, usize), Error> {
+    let mut fixable: Vec<(Arc<ValidationError>, Fix)> = module.errors.keys()
+        .filter_map(|error| fix_for(error, &module.source).map(|fix| (error.clone(), fix)))
+        .collect();
+
+    fixable.sort_by_key(|(error, _)| error.location().start);
+
+    let fixes: Vec<Fix> = fixable.iter().map(|(_, fix)| fix.clone()).collect();
+    let (source, applied) = apply_fixes(&module.source, &fixes);
+
+    if applied.is_empty() {
+        return Ok((module.clone(), 0));
+    }
+
+    File::create(&module.path)?.write_all(source.as_bytes())?;
+
+    let first_edit = applied.iter()
+        .flat_map(|index| fixes[*index].edits.iter().map(|edit| edit.start))
+        .min()
+        .unwrap_or(0);
+
+    let mut errors = module.errors.clone();
+
+    for index in &applied {
+        errors.remove(&fixable[*index].0);
+    }
+
+    errors.retain(|error, _| error.location().start < first_edit);
+
+    Ok((ModuleRecord { source, errors, ..module.clone() }, applied.len()))
+}
+
+// the offsets of the name inside of the location, as a whole word
+fn find_word(source: &str, location: &Location, word: &str) -> Option<(u32, u32)> {
+    let text = source.get(location.start as usize..location.end as usize)?;
+
+    text.match_indices(word)
+        .find(|(index, _)| !text[..*index].ends_with(is_identifier_character) && !text[index + word.len()..].starts_with(is_identifier_character))
+        .map(|(index, _)| (location.start + index as u32, location.start + (index + word.len()) as u32))
+}
+
+fn followed_by_as(rest: &str) -> bool {
+    let rest = rest.trim_start();
+
+    rest.starts_with("as") && !rest[2..].starts_with(is_identifier_character)
+}
+
+fn is_import(source: &str, location: &Location) -> bool {
+    source.get(location.start as usize..).map(|rest| rest.starts_with("import")).unwrap_or(false)
+}
+
+// the operator token after the left operand, which the location starts at. Only whitespace,
+// comments and closing parentheses can be in front of it
+fn operator_offset(source: &str, location: &Location, operator: &str) -> Option<u32> {
+    let mut offset = location.start as usize;
+
+    loop {
+        let rest = source.get(offset..location.end as usize)?;
+        let trimmed = rest.trim_start_matches(|character: char| character.is_whitespace() || character == ')');
+
+        offset += rest.len() - trimmed.len();
+
+        if trimmed.starts_with("/*") {
+            offset += trimmed.find("*/")? + 2;
+        } else if trimmed.starts_with("//") {
+            offset += trimmed.find('\n')?;
+        } else if trimmed.starts_with(operator) && !trimmed[operator.len()..].starts_with('=') {
+            return Some(offset as u32);
+        } else {
+            return None;
+        }
+    }
+}
+
+fn is_identifier_character(character: char) -> bool {
+    character.is_alphanumeric() || character == '_' || character == '$'
+}
+
+// `if (1 === 1) { ... }` without an else branch keeps only the block, `if (1 === 2) { ... }`
+// is removed with its block
+fn remove_condition(source: &str, location: &Location, outcome: bool) -> Option<Fix> {
+    let before = source.get(..location.start as usize)?.trim_end();
+
+    if !before.ends_with('(') {
+        return None;
+    }
+
+    let before = before[..before.len() - 1].trim_end();
+
+    if !before.ends_with("if") || before[..before.len() - 2].ends_with(is_identifier_character) {
+        return None;
+    }
+
+    let if_start = before.len() - 2;
+    let after = source.get(location.end as usize..)?;
+    let after_test = after.trim_start();
+
+    if !after_test.starts_with(')') {
+        return None;
+    }
+
+    let after_paren = after_test[1..].trim_start();
+
+    if !after_paren.starts_with('{') {
+        return None;
+    }
+
+    let block_start = source.len() - after_paren.len();
+    let block_end = matching_brace(source, block_start)?;
+    let rest = source[block_end..].trim_start();
+
+    if rest.starts_with("else") && !rest[4..].starts_with(is_identifier_character) {
+        return None;
+    }
+
+    if outcome {
+        return Some(Fix::single(String::from("remove the condition"), if_start as u32, block_start as u32, String::new()));
+    }
+
+    // the line goes with the statement when nothing else is on it
+    let line_start = source[..if_start].rfind('\n').map(|index| index + 1).unwrap_or(0);
+    let line_end = source[block_end..].find('\n').map(|index| block_end + index + 1).unwrap_or(source.len());
+
+    let (start, end) = if source[line_start..if_start].trim().is_empty() && source[block_end..line_end].trim().is_empty() {
+        (line_start, line_end)
+    } else {
+        (if_start, block_end)
+    };
+
+    Some(Fix::single(String::from("remove the if statement"), start as u32, end as u32, String::new()))
+}
+
+// the offset after the brace closing the one at start, strings and comments are skipped
+fn matching_brace(source: &str, start: usize) -> Option<usize> {
+    let characters: Vec<(usize, char)> = source[start..].char_indices().collect();
+    let mut depth = 0;
+    let mut index = 0;
+
+    while let Some(&(offset, character)) = characters.get(index) {
+        let next = characters.get(index + 1).map(|(_, character)| *character);
+
+        match character {
+            '{' => depth += 1,
+
+            '}' => {
+                depth -= 1;
+
+                if depth == 0 {
+                    return Some(start + offset + 1);
+                }
+            },
+
+            '"' | '\'' | '`' => {
+                index += 1;
+
+                while let Some(&(_, inner)) = characters.get(index) {
+                    if inner == '\\' {
+                        index += 1;
+                    } else if inner == character {
+                        break;
+                    }
+
+                    index += 1;
+                }
+            },
+
+            '/' if next == Some('/') => {
+                while characters.get(index).map(|(_, character)| *character != '\n').unwrap_or(false) {
+                    index += 1;
+                }
+            },
+
+            '/' if next == Some('*') => {
+                index += 2;
+
+                while index < characters.len() && !(characters[index].1 == '*' && characters.get(index + 1).map(|(_, character)| *character) == Some('/')) {
+                    index += 1;
+                }
+
+                index += 1;
+            },
+
+            _ => (),
+        }
+
+        index += 1;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_fixes_from_the_end_of_the_source() {
+        let fixes = vec!(
+            Fix::single(String::from("second"), 10, 12, String::from("===")),
+            Fix::single(String::from("first"), 2, 4, String::from("!==")),
+        );
+
+        assert_eq!(apply_fixes("a != b; c == d", &fixes), (String::from("a !== b; c === d"), vec!(0, 1)));
+    }
+
+    #[test]
+    fn skips_fixes_overlapping_an_earlier_one() {
+        let fixes = vec!(
+            Fix::single(String::from("inner"), 4, 6, String::from("y")),
+            Fix::single(String::from("outer"), 0, 5, String::from("z")),
+            Fix::single(String::from("touching"), 5, 7, String::from("w")),
+        );
+
+        assert_eq!(apply_fixes("abcdefgh", &fixes), (String::from("zwh"), vec!(1, 2)));
+    }
+}
@@ -0,0 +1,361 @@
+use ratel::ast as Ast;
+use failure::*;
+use dynamic_typing::{ Type, FunctionType, FunctionKind, Variable, VariableKind, Scope, Scoped, ScopeRef, new_mutex_ref, CustomType };
+use expressions::determine_expression_type;
+use classes::{ type_from_class, class_expression_from };
+
+// binds each parameter to the type of the value passed at its position, if it is known
+pub fn typed_function_arguments(params: &Ast::PatternList, argument_types: &[Type]) -> Vec<Variable> {
+    params.iter()
+        .enumerate()
+        .flat_map(|(index, param)| {
+            let argument_type = match param.item {
+                // the values inside of destructured arguments are not followed
+                Ast::Pattern::ObjectPattern { .. } | Ast::Pattern::ArrayPattern { .. } => Type::Undefined,
+                _ => argument_types.get(index).cloned().unwrap_or(Type::Undefined),
+            };
+
+            pattern_names(&param.item).into_iter()
+                .map(move |name| Variable::new(name, argument_type.clone(), VariableKind::Let))
+        })
+        .collect()
+}
+
+// a this_type of None keeps the this binding of the parent scope, like arrow functions do
+pub fn function_scope(arguments: &[Variable], this_type: Option<Type>, parent: &ScopeRef) -> ScopeRef {
+    let mut scope = Scope::new(String::from("FunctionScope"), Some(parent.clone()));
+
+    for argument in arguments {
+        scope.add(argument.clone());
+    }
+
+    if let Some(this_type) = this_type {
+        scope.set_this_type(this_type);
+    }
+
+    new_mutex_ref(scope)
+}
+
+// function literals typed without their node only know where their parameters or body start,
+// doc comments of functions are otherwise looked up by the line the function starts on
+pub fn literal_start(params: &Ast::PatternList, body_start: u32) -> u32 {
+    match params.iter().next() {
+        Some(param) => param.start,
+        None => body_start,
+    }
+}
+
+// parameters documented with @param are declared with that type, everything else keeps the passed type
+pub fn annotated_arguments(params: &Ast::PatternList, argument_types: &[Type], offset: u32, scope: &ScopeRef) -> Vec<Variable> {
+    let arguments = typed_function_arguments(params, argument_types);
+    let annotations = match scope.annotations() {
+        Some(annotations) => annotations,
+        None => return arguments,
+    };
+
+    let comment = match annotations.at(offset) {
+        Some(comment) => comment,
+        None => return arguments,
+    };
+
+    arguments.into_iter()
+        .map(|mut argument| {
+            let declared_type = comment.params.get(argument.name())
+                .and_then(|type_expression| annotations.resolve_type(type_expression, scope));
+
+            // the values of parameters come from calls, which are checked against the declared
+            // type, so inside of the function that is what they are inferred as
+            if let Some(declared_type) = declared_type {
+                argument = Variable::new(argument.name().to_owned(), declared_type.clone(), VariableKind::Let);
+                argument.set_declared_type(declared_type);
+            }
+
+            argument
+        })
+        .collect()
+}
+
+pub fn declared_return_type(offset: u32, scope: &ScopeRef) -> Option<Type> {
+    let annotations = scope.annotations()?;
+    let type_expression = annotations.at(offset)?.returns.clone()?;
+
+    annotations.resolve_type(&type_expression, scope)
+}
+
+pub fn function_type_from(name: Option<String>, params: &Ast::PatternList, body: &Ast::BlockNode<Ast::Statement>, this_type: Option<Type>, start: u32, scope: &ScopeRef) -> Result<FunctionType, Error> {
+    function_type_with(name, annotated_arguments(params, &[], start, scope), body, this_type, start, scope)
+}
+
+pub fn arrow_type_from(params: &Ast::PatternList, body: &Ast::expression::ArrowBody, start: u32, scope: &ScopeRef) -> Result<FunctionType, Error> {
+    arrow_type_with(annotated_arguments(params, &[], start, scope), body, start, scope)
+}
+
+// types a function literal that is passed to a call, with its parameters bound to the values the callee passes in
+pub fn callback_type_from(expression: &Ast::Expression, start: u32, argument_types: &[Type], scope: &ScopeRef) -> Result<Type, Error> {
+    let function_type = match expression {
+        Ast::Expression::Arrow(arrow_expression) => {
+            let arguments = annotated_arguments(&arrow_expression.params, argument_types, start, scope);
+
+            arrow_type_with(arguments, &arrow_expression.body, start, scope)?
+        },
+
+        Ast::Expression::Function(function) => {
+            let Ast::OptionalName(name) = function.name;
+            let name = name.map(|name| name.item.to_string());
+            let arguments = annotated_arguments(&function.params, argument_types, start, scope);
+
+            function_type_with(name, arguments, &function.body, Some(Type::Undefined), start, scope)?
+        },
+
+        _ => return determine_expression_type(expression, scope),
+    };
+
+    Ok(Type::from(function_type))
+}
+
+pub fn arrow_body_start(body: &Ast::expression::ArrowBody) -> u32 {
+    match body {
+        Ast::expression::ArrowBody::Block(block) => block.start,
+        Ast::expression::ArrowBody::Expression(expression) => expression.start,
+    }
+}
+
+// a documented return type takes precedence, mismatches with the body are reported by the validation pass
+fn function_type_with(name: Option<String>, arguments: Vec<Variable>, body: &Ast::Block<Ast::Statement>, this_type: Option<Type>, offset: u32, scope: &ScopeRef) -> Result<FunctionType, Error> {
+    let local_scope = function_scope(&arguments, this_type, scope);
+    let mut function_type = FunctionType::new(arguments);
+
+    if let Some(name) = name {
+        function_type.assign_name(name);
+    }
+
+    let return_type = match declared_return_type(offset, scope) {
+        Some(return_type) => return_type,
+        None => return_type_of(body, &local_scope),
+    };
+
+    function_type.set_return_type(return_type);
+
+    Ok(function_type)
+}
+
+fn arrow_type_with(arguments: Vec<Variable>, body: &Ast::expression::ArrowBody, offset: u32, scope: &ScopeRef) -> Result<FunctionType, Error> {
+    let local_scope = function_scope(&arguments, None, scope);
+
+    let return_type = match declared_return_type(offset, scope) {
+        Some(return_type) => return_type,
+        None => arrow_return_type(body, &local_scope),
+    };
+
+    let mut function_type = FunctionType::new(arguments);
+
+    function_type.set_kind(FunctionKind::Arrow);
+    function_type.set_return_type(return_type);
+
+    Ok(function_type)
+}
+
+pub fn arrow_return_type(body: &Ast::expression::ArrowBody, local_scope: &ScopeRef) -> Type {
+    match body {
+        Ast::expression::ArrowBody::Block(block) => return_type_of(block, &local_scope),
+
+        // errors inside of the function body are reported by the validation pass
+        Ast::expression::ArrowBody::Expression(expression) => {
+            determine_expression_type(expression, &local_scope).unwrap_or(Type::Undefined)
+        },
+    }
+}
+
+pub fn function_expression_from<'ast>(function: Ast::Function<'ast, Ast::MandatoryName<'ast>>) -> Ast::Function<'ast, Ast::OptionalName<'ast>> {
+    let Ast::MandatoryName(name) = function.name;
+
+    Ast::Function { name: Ast::OptionalName(Some(name)), generator: function.generator, params: function.params, body: function.body }
+}
+
+pub fn return_type_of(body: &Ast::Block<Ast::Statement>, scope: &ScopeRef) -> Type {
+    let mut types = vec!();
+
+    collect_block_return_types(body.body.iter().map(|statement| &statement.item), scope, &mut types);
+
+    match types.len() {
+        0 => Type::Undefined,
+        _ => Type::union(types),
+    }
+}
+
+// every block gets its own scope, so return statements can see the locals declared before them
+fn collect_block_return_types<'a, 'ast: 'a, I>(statements: I, parent: &ScopeRef, types: &mut Vec<Type>)
+    where I: Iterator<Item = &'a Ast::Statement<'ast>>
+{
+    let mut scope = block_scope(parent);
+
+    for statement in statements {
+        declare_locals(statement, &mut scope);
+        collect_return_types(statement, &scope, types);
+    }
+}
+
+fn collect_return_types(statement: &Ast::Statement, scope: &ScopeRef, types: &mut Vec<Type>) {
+    match statement {
+        Ast::Statement::Return(return_statement) => {
+            let return_type = match return_statement.value {
+                // errors inside of the function body are reported by the validation pass
+                Some(value) => determine_expression_type(&value, scope).unwrap_or(Type::Undefined),
+                None => Type::Undefined,
+            };
+
+            types.push(return_type);
+        },
+
+        Ast::Statement::Block(block) => collect_block_return_types(block.body.iter().map(|statement| &statement.item), scope, types),
+
+        Ast::Statement::If(if_statement) => {
+            collect_return_types(&if_statement.consequent.item, scope, types);
+
+            if let Some(alternate) = if_statement.alternate {
+                collect_return_types(&alternate.item, scope, types);
+            }
+        },
+
+        Ast::Statement::While(while_statement) => collect_return_types(&while_statement.body.item, scope, types),
+        Ast::Statement::Do(do_statement) => collect_return_types(&do_statement.body.item, scope, types),
+        Ast::Statement::Labeled(labeled_statement) => collect_return_types(&labeled_statement.body.item, scope, types),
+
+        Ast::Statement::For(for_statement) => {
+            let mut scope = block_scope(scope);
+
+            if let Some(init) = for_statement.init {
+                declare_loop_variables(&init.item, &mut scope);
+            }
+
+            collect_return_types(&for_statement.body.item, &scope, types);
+        },
+
+        Ast::Statement::ForIn(for_statement) => {
+            let mut scope = block_scope(scope);
+
+            declare_loop_variables(&for_statement.left.item, &mut scope);
+            collect_return_types(&for_statement.body.item, &scope, types);
+        },
+
+        Ast::Statement::ForOf(for_statement) => {
+            let mut scope = block_scope(scope);
+
+            declare_loop_variables(&for_statement.left.item, &mut scope);
+            collect_return_types(&for_statement.body.item, &scope, types);
+        },
+
+        Ast::Statement::Try(try_statement) => {
+            collect_block_return_types(try_statement.block.body.iter().map(|statement| &statement.item), scope, types);
+
+            if let Some(handler) = try_statement.handler {
+                let mut scope = block_scope(scope);
+
+                // anything can be thrown, so the type of the caught value is unknown
+                for name in pattern_names(&handler.param.item) {
+                    scope.add(Variable::new(name, Type::Undefined, VariableKind::Let));
+                }
+
+                collect_block_return_types(handler.body.body.iter().map(|statement| &statement.item), &scope, types);
+            }
+
+            if let Some(finalizer) = try_statement.finalizer {
+                collect_block_return_types(finalizer.body.iter().map(|statement| &statement.item), scope, types);
+            }
+        },
+
+        // all cases of a switch share one block
+        Ast::Statement::Switch(switch_statement) => {
+            let statements = switch_statement.cases.body.iter()
+                .flat_map(|case| case.consequent.iter().map(|statement| &statement.item));
+
+            collect_block_return_types(statements, scope, types);
+        },
+
+        // return statements of nested functions and classes belong to them
+        _ => (),
+    }
+}
+
+fn block_scope(parent: &ScopeRef) -> ScopeRef {
+    new_mutex_ref(Scope::new(String::from("BlockScope"), Some(parent.clone())))
+}
+
+fn declare_locals(statement: &Ast::Statement, scope: &mut ScopeRef) {
+    match statement {
+        Ast::Statement::Declaration(declaration) => declare_variables(declaration, scope),
+
+        Ast::Statement::Function(function) => {
+            let Ast::MandatoryName(name) = function.name;
+            let function_type = function_type_from(Some(name.item.to_string()), &function.params, &function.body, Some(Type::Undefined), name.start, scope);
+
+            if let Ok(function_type) = function_type {
+                scope.add(Variable::new(name.item.to_string(), Type::Function(new_mutex_ref(function_type)), VariableKind::Let));
+            }
+        },
+
+        Ast::Statement::Class(class) => {
+            let Ast::MandatoryName(name) = class.name;
+
+            if let Ok(class_type) = type_from_class(&class_expression_from(*class), scope) {
+                scope.add(Variable::new(name.item.to_string(), class_type, VariableKind::Let));
+            }
+        },
+
+        _ => (),
+    }
+}
+
+fn declare_loop_variables(init: &Ast::statement::ForInit, scope: &mut ScopeRef) {
+    if let Ast::statement::ForInit::Declaration(declaration) = init {
+        declare_variables(declaration, scope);
+    }
+}
+
+// the values of destructured declarations are not followed, their names are bound without a type
+fn declare_variables(declaration: &Ast::statement::DeclarationStatement, scope: &mut ScopeRef) {
+    for declarator in declaration.declarators.iter() {
+        let declared_type = match (declarator.id.item, declarator.init) {
+            // errors in the value are reported by the validation pass
+            (Ast::Pattern::Identifier(_), Some(value)) => determine_expression_type(&value, scope).unwrap_or(Type::Undefined),
+            _ => Type::Undefined,
+        };
+
+        for name in pattern_names(&declarator.id.item) {
+            scope.add(Variable::new(name, declared_type.clone(), VariableKind::from(declaration.kind)));
+        }
+    }
+}
+
+// all names a pattern binds, destructuring patterns bind every identifier inside of them
+pub fn pattern_names(pattern: &Ast::Pattern) -> Vec<String> {
+    match pattern {
+        Ast::Pattern::Identifier(name) => vec!((*name).to_string()),
+        Ast::Pattern::RestElement { argument } => vec!(argument.item.to_string()),
+        Ast::Pattern::AssignmentPattern { left, .. } => pattern_names(&left.item),
+        Ast::Pattern::ObjectPattern { properties } => properties.iter().flat_map(|property| property_names(&property.item)).collect(),
+        Ast::Pattern::ArrayPattern { elements } => elements.iter().flat_map(|element| pattern_names(&element.item)).collect(),
+        Ast::Pattern::Void => vec!(),
+    }
+}
+
+// the values of object patterns are parsed as expressions, e.g. `{ a, b: c, d = 1, ...e }`
+fn property_names(property: &Ast::Property) -> Vec<String> {
+    match property {
+        Ast::Property::Shorthand(name) => vec!((*name).to_string()),
+        Ast::Property::Literal { value, .. } => expression_names(&value.item),
+        Ast::Property::Spread { argument } => expression_names(&argument.item),
+        Ast::Property::Method { .. } => vec!(),
+    }
+}
+
+fn expression_names(expression: &Ast::Expression) -> Vec<String> {
+    match expression {
+        Ast::Expression::Identifier(name) => vec!((*name).to_string()),
+        Ast::Expression::Binary(binary) if binary.operator == Ast::OperatorKind::Assign => expression_names(&binary.left.item),
+        Ast::Expression::Object(object) => object.body.iter().flat_map(|property| property_names(&property.item)).collect(),
+        Ast::Expression::Array(array) => array.body.iter().flat_map(|element| expression_names(&element.item)).collect(),
+        Ast::Expression::Spread(spread) => expression_names(&spread.argument.item),
+        _ => vec!(),
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::{ HashMap, HashSet };
+use dynamic_typing::{ Type, ObjectType, CustomTypeObject, Scoped, ScopeRef, SafeBorrow };
+use statics::OBJECT_PROTOTYPE;
+use arrays::array_of;
+use classes::instance_of;
+
+#[derive(Debug, Default, Clone)]
+pub struct DocComment {
+    pub type_expression: Option<String>,
+    pub params: HashMap<String, String>,
+    pub returns: Option<String>,
+    pub typedef: Option<(String, String)>,
+    pub properties: Vec<(String, String)>,
+}
+
+// the JSDoc blocks of a source file, each one annotates the code on the line following it
+#[derive(Debug, Default)]
+pub struct Annotations {
+    comments: HashMap<usize, DocComment>,
+    typedefs: HashMap<String, DocComment>,
+    line_starts: Vec<u32>,
+}
+
+impl Annotations {
+    pub fn parse(source: &str) -> Self {
+        let mut annotations = Annotations::default();
+
+        annotations.line_starts.push(0);
+        annotations.line_starts.extend(source.match_indices('\n').map(|(index, _)| index as u32 + 1));
+
+        let mut rest = source;
+        let mut offset = 0;
+
+        while let Some(start) = rest.find("/**") {
+            let end = match rest[start..].find("*/") {
+                Some(end) => start + end,
+                None => break,
+            };
+
+            let comment = parse_comment(&rest[start + 3..end]);
+            let after_comment = &rest[end + 2..];
+            let code_start = offset + end + 2 + (after_comment.len() - after_comment.trim_start().len());
+            let line = annotations.line_of(code_start as u32);
+
+            if let Some((ref name, _)) = comment.typedef {
+                annotations.typedefs.insert(name.clone(), comment.clone());
+            }
+
+            annotations.comments.insert(line, comment);
+
+            offset += end + 2;
+            rest = &rest[end + 2..];
+        }
+
+        annotations
+    }
+
+    // the doc comment annotating the line of the given source offset
+    pub fn at(&self, offset: u32) -> Option<&DocComment> {
+        self.comments.get(&self.line_of(offset))
+    }
+
+    // names that are neither built in, a typedef nor a class in scope make the whole expression
+    // unknown, the annotation is ignored then
+    pub fn resolve_type(&self, expression: &str, scope: &ScopeRef) -> Option<Type> {
+        self.resolve(expression, scope, &mut HashSet::new())
+    }
+
+    fn line_of(&self, offset: u32) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        }
+    }
+
+    // typedefs that are currently being resolved end up in `resolving`, to stop recursive definitions
+    fn resolve(&self, expression: &str, scope: &ScopeRef, resolving: &mut HashSet<String>) -> Option<Type> {
+        let members = split_top_level(expression, '|').iter()
+            .map(|member| self.resolve_member(member.trim(), scope, resolving))
+            .collect::<Option<Vec<Type>>>()?;
+
+        Some(Type::union(members))
+    }
+
+    fn resolve_member(&self, expression: &str, scope: &ScopeRef, resolving: &mut HashSet<String>) -> Option<Type> {
+        if expression.starts_with('(') && expression.ends_with(')') {
+            return self.resolve(&expression[1..expression.len() - 1], scope, resolving);
+        }
+
+        if expression.starts_with('?') && expression.len() > 1 {
+            return Some(Type::union(vec!(self.resolve(&expression[1..], scope, resolving)?, Type::Null)));
+        }
+
+        if expression.ends_with("[]") {
+            return Some(array_of(self.resolve(&expression[..expression.len() - 2], scope, resolving)?));
+        }
+
+        if expression.starts_with('{') && expression.ends_with('}') {
+            let mut properties = HashMap::new();
+
+            for property in split_top_level(&expression[1..expression.len() - 1], ',') {
+                let mut parts = property.splitn(2, ':');
+                let name = parts.next().unwrap_or("").trim();
+                let property_type = match parts.next() {
+                    Some(property_type) => self.resolve(property_type, scope, resolving)?,
+                    None => Type::Undefined,
+                };
+
+                properties.insert(name.to_owned(), property_type);
+            }
+
+            return Some(Type::from(ObjectType::new(None, properties, Some(CustomTypeObject::from(&*OBJECT_PROTOTYPE)))));
+        }
+
+        if let Some(generic_start) = expression.find('<') {
+            let container = expression[..generic_start].trim_end_matches('.');
+            let argument = expression[generic_start + 1..].trim_end_matches('>');
+
+            if container == "Array" {
+                return Some(array_of(self.resolve(argument, scope, resolving)?));
+            }
+
+            return self.resolve_member(container, scope, resolving);
+        }
+
+        match expression {
+            "number" | "Number" => Some(Type::Number),
+            "string" | "String" => Some(Type::String),
+            "boolean" | "Boolean" => Some(Type::Boolean),
+            "RegExp" => Some(Type::RegExp),
+            "null" => Some(Type::Null),
+            "Object" | "object" => Some(Type::from(ObjectType::new(None, HashMap::new(), Some(CustomTypeObject::from(&*OBJECT_PROTOTYPE))))),
+            "Array" => Some(array_of(Type::Undefined)),
+
+            // undefined doubles as the type that accepts everything
+            "*" | "?" | "any" | "undefined" | "void" => Some(Type::Undefined),
+
+            _ => self.resolve_named(expression, scope, resolving),
+        }
+    }
+
+    // placeholders like [type] are unknown names as well
+    fn resolve_named(&self, name: &str, scope: &ScopeRef, resolving: &mut HashSet<String>) -> Option<Type> {
+        if let Some(typedef) = self.typedefs.get(name) {
+            if !resolving.insert(name.to_owned()) {
+                return Some(Type::Undefined);
+            }
+
+            let typedef_type = self.resolve_typedef(name, typedef, scope, resolving);
+
+            resolving.remove(name);
+
+            return typedef_type;
+        }
+
+        // classes and constructor functions describe their instances
+        let variable = scope.locate(name).ok()?;
+        let constructor_type = variable.borrow_safe(|variable| variable.current_type().clone());
+
+        instance_of(&constructor_type).ok()
+    }
+
+    fn resolve_typedef(&self, name: &str, typedef: &DocComment, scope: &ScopeRef, resolving: &mut HashSet<String>) -> Option<Type> {
+        let base_expression = match typedef.typedef {
+            Some((_, ref base_expression)) => base_expression.as_str(),
+            None => "Object",
+        };
+
+        if typedef.properties.is_empty() {
+            return self.resolve(base_expression, scope, resolving);
+        }
+
+        let properties = typedef.properties.iter()
+            .map(|(property_name, property_type)| Some((property_name.clone(), self.resolve(property_type, scope, resolving)?)))
+            .collect::<Option<HashMap<String, Type>>>()?;
+
+        Some(Type::from(ObjectType::new(Some(name.to_owned()), properties, Some(CustomTypeObject::from(&*OBJECT_PROTOTYPE)))))
+    }
+}
+
+fn parse_comment(body: &str) -> DocComment {
+    let mut comment = DocComment::default();
+
+    // tags may span multiple lines, so the leading stars are removed before splitting on @
+    let text: String = body.lines()
+        .map(|line| line.trim_start().trim_start_matches('*').trim())
+        .collect::<Vec<&str>>()
+        .join(" ");
+
+    for tag in text.split('@').skip(1) {
+        let tag_name: String = tag.chars().take_while(|character| character.is_alphanumeric()).collect();
+        let rest = tag[tag_name.len()..].trim();
+        let (type_expression, rest) = match braced(rest) {
+            Some((type_expression, rest)) => (type_expression, rest),
+            None => continue,
+        };
+
+        let name = rest.split_whitespace().next()
+            .map(|name| name.trim_matches(|character| character == '[' || character == ']'))
+            .map(|name| name.split('=').next().unwrap_or(name).to_owned());
+
+        match (tag_name.as_str(), name) {
+            ("type", _) => comment.type_expression = Some(type_expression),
+            ("returns", _) | ("return", _) => comment.returns = Some(type_expression),
+            ("param", Some(name)) | ("arg", Some(name)) | ("argument", Some(name)) => {
+                comment.params.insert(name, type_expression);
+            },
+            ("typedef", Some(name)) => comment.typedef = Some((name, type_expression)),
+            ("property", Some(name)) | ("prop", Some(name)) => comment.properties.push((name, type_expression)),
+            _ => (),
+        }
+    }
+
+    comment
+}
+
+// splits `{type} rest` into the type expression and the rest, braces inside the type may be nested
+fn braced(text: &str) -> Option<(String, &str)> {
+    if !text.starts_with('{') {
+        return None;
+    }
+
+    let mut depth = 0;
+
+    for (index, character) in text.char_indices() {
+        match character {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+
+                if depth == 0 {
+                    return Some((text[1..index].trim().to_owned(), text[index + 1..].trim()));
+                }
+            },
+            _ => (),
+        }
+    }
+
+    None
+}
+
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = vec!();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, character) in text.char_indices() {
+        match character {
+            '{' | '(' | '<' | '[' => depth += 1,
+            '}' | ')' | '>' | ']' => depth -= 1,
+            _ if character == separator && depth == 0 => {
+                parts.push(&text[start..index]);
+                start = index + 1;
+            },
+            _ => (),
+        }
+    }
+
+    parts.push(&text[start..]);
+    parts.into_iter().filter(|part| !part.trim().is_empty()).collect()
+}
@@ -6,6 +6,7 @@ extern crate lazy_static;
 extern crate serde_derive;
 extern crate serde;
 extern crate serde_json;
+extern crate toml;
 #[macro_use]
 extern crate erased_serde;
 extern crate uuid;
@@ -27,10 +28,25 @@ mod ast_nodes;
 mod meta_data_collection;
 mod expression_meta_data;
 mod context;
-
+mod functions;
+mod arrays;
+mod classes;
+mod jsdoc;
+mod declarations;
+mod modules;
+mod config;
+mod output;
+mod suppression;
+mod baseline;
+mod suggestions;
+mod fixes;
+
+use std::collections::HashMap;
+use std::env;
+use std::process;
 use std::fs::File;
 use std::io::prelude::*;
-use std::iter::repeat;
+use std::path::Path;
 use ratel::{ parse, ast as Ast };
 use failure::*;
 
@@ -38,11 +54,20 @@ use dynamic_typing::{
     Type, Scope, Variable, VariableKind, CustomTypeObject, MutexRef,
     Scoped, new_mutex_ref, ScopeRef
 };
-use statics::OBJECT;
 use tracing::tracing_pass;
 use expressions::determine_expression_type;
+use classes::{ type_from_class, class_expression_from };
+use functions::function_type_from;
 use validation::validation_pass;
-use error::ValidationError;
+use jsdoc::Annotations;
+use config::Config;
+use declarations::{ load_declarations, emit_declarations, emit_module_declarations, emit_commonjs_declarations };
+use error::ErrorVec;
+use modules::{ ModuleGraph, ModuleRecord, extract_module_syntax, commonjs_scope, commonjs_exports };
+use output::{ OutputFormat, report_diagnostics };
+use suppression::Suppressions;
+use baseline::Baseline;
+use fixes::fix_module;
 use std::sync::Arc;
 
 use context::Context;
@@ -50,118 +75,339 @@ use expression_meta_data::MetaHashMap;
 
 
 fn main() {
-    let global_object: Variable = Variable::new(String::from("Object"), (&*OBJECT).clone(), VariableKind::Const);
+    let mut declaration_inputs = vec!();
+    let mut declaration_output = None;
+    let mut entry_path = None;
+    let mut format = OutputFormat::Text;
+    let mut baseline_input = None;
+    let mut baseline_output = None;
+    let mut apply_fixes = false;
+    let mut arguments = env::args().skip(1);
+
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--emit-declarations" => declaration_output = arguments.next(),
+            "--baseline" => baseline_input = arguments.next(),
+            "--write-baseline" => baseline_output = arguments.next(),
+            "--fix" => apply_fixes = true,
+
+            "--format" => match arguments.next().as_ref().and_then(|name| OutputFormat::from_name(name)) {
+                Some(output_format) => format = output_format,
+                None => { eprintln!("--format expects one of text, json or lsp"); process::exit(2); }
+            },
+
+            _ if argument.ends_with(".d.ts") => declaration_inputs.push(argument),
+            _ => entry_path = Some(argument),
+        }
+    }
+
+    let entry_path = entry_path.unwrap_or_else(|| String::from("/Users/Jovan/rusty/test.js"));
+    let config = Arc::new(match Config::discover(Path::new(&entry_path)) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("Error while reading {}: {}", config::CONFIG_FILE, error);
+            Config::default()
+        }
+    });
 
     let mut static_root_scope = Scope::new(String::from("StaticRoot"), None);
 
-    static_root_scope.add(global_object);
+    for global in config.globals() {
+        static_root_scope.add(global);
+    }
 
     let static_root_scope_ref = new_mutex_ref(static_root_scope);
 
-    // read test.js
-    let mut file = File::open("/Users/Jovan/rusty/test.js").unwrap();
-    let mut contents = String::new();
-    file.read_to_string(&mut contents).unwrap();
+    // types of libraries we call into
+    let mut declaration_scope = Scope::new(String::from("Declarations"), Some(static_root_scope_ref.clone()));
+    let mut declared_modules: HashMap<String, Type> = HashMap::new();
+
+    for path in declaration_inputs {
+        let mut source = String::new();
+
+        if let Err(error) = File::open(&path).and_then(|mut file| file.read_to_string(&mut source)) {
+            eprintln!("Error while reading declarations from {}: {}", path, error);
+            continue;
+        }
+
+        match load_declarations(&source, &static_root_scope_ref) {
+            Ok(declarations) => {
+                for global in declarations.globals {
+                    declaration_scope.add(global);
+                }
+
+                declared_modules.extend(declarations.modules);
+            },
 
-    let structured_content: Vec<&str> = contents.split('\n').collect();
+            Err(error) => eprintln!("Error while loading declarations from {}: {}", path, error),
+        }
+    }
+
+    let declaration_scope_ref = new_mutex_ref(declaration_scope);
+    let mut graph = ModuleGraph::new(declaration_scope_ref, declared_modules, config.clone(), analyze_module);
+
+    let entry = match graph.load(Path::new(&entry_path)) {
+        Some(entry) => entry,
+        None => { eprintln!("Unable to read module {}", entry_path); process::exit(2); }
+    };
+
+    // writes the inferred types of the entry module as a declaration file
+    if let Some(path) = declaration_output {
+        let output = match entry.exports_object {
+            Some(ref exports_object) if !entry.is_opaque() => emit_commonjs_declarations(exports_object),
+            _ if entry.is_module => emit_module_declarations(&entry.exports),
+            _ => emit_declarations(&entry.scope),
+        };
+
+        if let Err(error) = File::create(&path).and_then(|mut file| file.write_all(output.as_bytes())) {
+            eprintln!("Error while writing declarations {}: {}", path, error);
+            process::exit(2);
+        }
+    }
+
+    let mut reported_modules: Vec<Arc<ModuleRecord>> = graph.modules().into_iter()
+        .filter(|module| config.includes(&module.path))
+        .collect();
+
+    // records every current diagnostic instead of reporting them
+    if let Some(path) = baseline_output {
+        let baseline = Baseline::from_modules(&reported_modules, &config.directory);
+
+        match baseline.write(Path::new(&path)) {
+            Ok(()) => eprintln!("Wrote {} diagnostics to {}", baseline.diagnostic_count(), path),
+            Err(error) => { eprintln!("Error while writing baseline {}: {}", path, error); process::exit(2); }
+        }
+
+        return;
+    }
+
+    if let Some(path) = baseline_input {
+        let baseline = match Baseline::read(Path::new(&path)) {
+            Ok(baseline) => baseline,
+            Err(error) => { eprintln!("Error while reading baseline {}: {}", path, error); process::exit(2); }
+        };
+
+        reported_modules = reported_modules.into_iter()
+            .map(|module| Arc::new(ModuleRecord { errors: baseline.new_diagnostics(&module, &config.directory), ..(*module).clone() }))
+            .collect();
+    }
+
+    // the diagnostics that could not be fixed are still reported
+    if apply_fixes {
+        reported_modules = reported_modules.into_iter()
+            .map(|module| match fix_module(&module) {
+                Ok((fixed, 0)) => Arc::new(fixed),
+                Ok((fixed, count)) => { eprintln!("Fixed {} problems in {}", count, module.path.display()); Arc::new(fixed) },
+                Err(error) => { eprintln!("Error while fixing {}: {}", module.path.display(), error); module },
+            })
+            .collect();
+    }
+
+    // warnings and infos never fail the run
+    if report_diagnostics(&reported_modules, format) {
+        process::exit(1);
+    }
+}
+
+// runs every pass over a single module, the modules it imports are analyzed first
+fn analyze_module(graph: &mut ModuleGraph, path: &Path, contents: String) -> ModuleRecord {
+    let syntax = extract_module_syntax(&contents);
+    let is_module = !syntax.is_commonjs();
+    let mut validation_errors = ErrorVec::new();
+    let mut imports = vec!();
+
+    for import in &syntax.imports {
+        imports.extend(graph.import_bindings(import, path, &mut validation_errors));
+    }
+
+    // the other dependencies are loaded up front as well, so unresolved ones are reported even
+    // when the module does not parse
+    for (specifier, location) in syntax.forwarded_dependencies() {
+        if let Err(error) = graph.dependency(&specifier, path, &location) {
+            validation_errors.insert(Arc::new(error));
+        }
+    }
+
+    // CommonJS modules get `module`, `exports` and `require` from a scope around them
+    let parent_scope = if syntax.is_commonjs() {
+        commonjs_scope(graph, &syntax.requires, path)
+    } else {
+        graph.root_scope.clone()
+    };
 
     // parse it
-    let module = match parse(&contents) {
+    let module = match parse(&syntax.source) {
         Ok(ast) => ast,
-        Err(e) => { println!("{:#?}", e); return; }
+        Err(e) => {
+            eprintln!("{:#?}", e);
+
+            let module_scope = new_mutex_ref(Scope::new(String::from("ModuleScope"), Some(parent_scope)));
+
+            let mut errors = graph.config.classify(validation_errors);
+
+            // most of the module was not validated, so unused suppressions are expected here
+            Suppressions::parse(&contents).apply(&mut errors);
+
+            // importers can not know anything about a module that does not parse
+            return ModuleRecord { path: path.to_owned(), source: contents, scope: module_scope, exports: HashMap::new(), exports_object: Some(Type::Undefined), is_module, errors };
+        }
     };
 
     let module_body = module.body();
 
-    let (module_scope, errors) = analyze_ast(module_body, static_root_scope_ref);
+    let annotations = Annotations::parse(&syntax.source);
+    let (module_scope, errors) = analyze_ast(module_body, parent_scope, annotations, imports);
 
     for error in errors {
-        println!("Error while analyzing scope <{}>: {:?}", module_scope.name(), error);
+        eprintln!("Error while analyzing scope <{}>: {:?}", module_scope.name(), error);
     }
 
-    let (module_scope, tracing_errors) = tracing_pass(module_body, module_scope);
+    let (module_scope, tracing_errors) = tracing_pass(module_body, module_scope, graph.config.widening);
 
     for error in tracing_errors {
-        println!("Error while tracing scope <{}> for type changes: {:?}", module_scope.name(), error);
+        eprintln!("Error while tracing scope <{}> for type changes: {:?}", module_scope.name(), error);
     }
 
     let data_map = MetaHashMap::new();
-    let mut context = Context::new(module_scope, new_mutex_ref(data_map));
+    let mut context = Context::new(module_scope, new_mutex_ref(data_map), graph.config.clone());
 
     {
         validation_pass(module_body, &mut context);
     }
 
-    let mut validation_errors: Vec<Arc<ValidationError>> = context.errors.iter().map(|error| error.clone()).collect();
-
-    validation_errors.sort_by(|a, b| {
-        if a.location().start < b.location().start {
-            return std::cmp::Ordering::Less
-        }
-
-        if a.location().start > b.location().start {
-            return std::cmp::Ordering::Greater
-        }
-
-        std::cmp::Ordering::Equal
-    });
+    let (exports, exports_object) = if syntax.is_commonjs() {
+        let (exports_object, exports) = commonjs_exports(&context.scope);
 
-    println!("{}", validation_errors.len());
+        (exports, Some(exports_object))
+    } else {
+        (graph.module_exports(&syntax.exports, &declared_names(module_body), &context.scope, path, &mut validation_errors), None)
+    };
 
     for error in validation_errors {
-        let (line_start, column_start) = get_line_from_offset(error.location().start, &structured_content);
-        let (_, column_end) = get_line_from_offset(error.location().end, &structured_content);
-        let line_content = structured_content[line_start as usize];
-        let mut range = column_end - column_start;
-
-        if range < 0 {
-            range = column_end;
-        }
-
-        let padding = repeat(" ").take(column_start as usize).collect::<String>();
-        let locator = repeat("^").take(range as usize).collect::<String>();
+        context.report(error);
+    }
 
-        println!("Validation Error: {} at {}", error, get_line_from_offset_as_string(error.location().start, &structured_content));
-        println!("{}\n{}{}", line_content, padding, locator);
+    // suppressions that silence nothing are reported, so they do not outlive the code they were for
+    for unused in Suppressions::parse(&contents).apply(&mut context.errors) {
+        context.report(Arc::new(unused));
     }
 
+    ModuleRecord { path: path.to_owned(), source: contents, scope: context.scope.clone(), exports, exports_object, is_module, errors: context.errors }
 }
 
-fn analyze_ast<'a, 'b>(body: Ast::StatementList, static_root_scope: MutexRef<Scope>) -> (MutexRef<Scope>, Vec<Error>) {
+fn analyze_ast<'a, 'b>(body: Ast::StatementList, static_root_scope: MutexRef<Scope>, annotations: Annotations, imports: Vec<Variable>) -> (MutexRef<Scope>, Vec<Error>) {
+
+    let mut module_scope = Scope::new(String::from("ModuleScope"), Some(static_root_scope));
+
+    module_scope.set_annotations(annotations);
 
-    let module_scope = Scope::new(String::from("ModuleScope"), Some(static_root_scope));
     let mut module_scope_ref = new_mutex_ref(module_scope);
     let mut scope_errors = vec!();
 
+    // imported bindings exist before any statement of the module runs
+    for variable in imports {
+        register_variable_type(&variable, &mut module_scope_ref);
+        module_scope_ref.add(variable);
+    }
+
     for statement in body {
         let statement = **statement;
 
-        if let Ast::Statement::Declaration(declaration_statement) = statement.item {
-            let Ast::statement::DeclarationStatement { declarators: declarations, kind } = declaration_statement;
+        match statement.item {
+            Ast::Statement::Declaration(declaration_statement) => {
+                let Ast::statement::DeclarationStatement { declarators: declarations, kind } = declaration_statement;
 
-            for declaration in declarations {
-                let variable = analyze_declaration(declaration.item, kind, module_scope_ref.clone());
+                for declaration in declarations {
+                    let variable = analyze_declaration(declaration.item, kind, module_scope_ref.clone());
 
-                match variable {
-                    Ok(variable) => {
+                    match variable {
+                        Ok(variable) => {
+                            register_variable_type(&variable, &mut module_scope_ref);
+                            module_scope_ref.add(variable)
+                        },
+                        Err(e) => scope_errors.push(e),
+                    }
+                }
+            },
 
-                        match variable.current_type() {
-                            Type::Object(data) => module_scope_ref.add_type(CustomTypeObject::from(data)),
-                            Type::Function(data) => module_scope_ref.add_type(CustomTypeObject::from(data)),
-                            _ => ()
-                        };
+            Ast::Statement::Class(class) => {
+                let Ast::MandatoryName(name) = class.name;
+                let class_type = match type_from_class(&class_expression_from(class), &module_scope_ref) {
+                    Ok(class_type) => class_type,
+                    Err(e) => { scope_errors.push(e); continue; }
+                };
 
-                        module_scope_ref.add(variable)
-                    },
-                    Err(e) => scope_errors.push(e),
-                }
-            }
+                let variable = Variable::new(name.item.to_string(), class_type, VariableKind::Let);
+
+                register_variable_type(&variable, &mut module_scope_ref);
+                module_scope_ref.add(variable);
+            },
+
+            Ast::Statement::Function(function) => {
+                let Ast::MandatoryName(name) = function.name;
+                let function_type = match function_type_from(Some(name.item.to_string()), &function.params, &function.body, Some(Type::Undefined), statement.start, &module_scope_ref) {
+                    Ok(function_type) => Type::from(function_type),
+                    Err(e) => { scope_errors.push(e); continue; }
+                };
+
+                let variable = Variable::new(name.item.to_string(), function_type, VariableKind::Var);
+
+                register_variable_type(&variable, &mut module_scope_ref);
+                module_scope_ref.add(variable);
+            },
+
+            _ => (),
         }
     }
 
     (module_scope_ref, scope_errors)
 }
 
+// the names each top level statement declares, keyed by the offset the statement starts at
+fn declared_names(body: Ast::StatementList) -> HashMap<u32, Vec<String>> {
+    let mut names = HashMap::new();
+
+    for statement in body {
+        let statement = **statement;
+        let statement_names = match statement.item {
+            Ast::Statement::Declaration(declaration) => {
+                declaration.declarators.iter()
+                    .filter_map(|declarator| match declarator.id.item {
+                        Ast::Pattern::Identifier(name) => Some(name.to_string()),
+                        _ => None,
+                    })
+                    .collect()
+            },
+
+            Ast::Statement::Function(function) => {
+                let Ast::MandatoryName(name) = function.name;
+
+                vec!(name.item.to_string())
+            },
+
+            Ast::Statement::Class(class) => {
+                let Ast::MandatoryName(name) = class.name;
+
+                vec!(name.item.to_string())
+            },
+
+            _ => vec!(),
+        };
+
+        names.insert(statement.start, statement_names);
+    }
+
+    names
+}
+
+fn register_variable_type(variable: &Variable, scope: &mut ScopeRef) {
+    match variable.current_type() {
+        Type::Object(data) => scope.add_type(CustomTypeObject::from(data)),
+        Type::Function(data) => scope.add_type(CustomTypeObject::from(data)),
+        _ => ()
+    };
+}
+
 fn analyze_declaration(declaration: Ast::Declarator, kind: Ast::DeclarationKind, scope: ScopeRef) -> Result<Variable, Error> {
     let variable_name = match declaration.id.item {
         Ast::Pattern::Identifier(name) => name.to_string(),
@@ -186,6 +432,14 @@ fn analyze_declaration(declaration: Ast::Declarator, kind: Ast::DeclarationKind,
         Ast::Pattern::Void => unreachable!("void pattern should only appear inside of array patterns!"),
     };
 
+    // a documented @type becomes the declared type, mismatches are reported during validation
+    let declared_type = scope.annotations()
+        .and_then(|annotations| {
+            let type_expression = annotations.at(declaration.id.start)?.type_expression.clone()?;
+
+            annotations.resolve_type(&type_expression, &scope)
+        });
+
     let mut variable_type = match declaration.init {
         Some(value) => determine_expression_type(&value, &scope)?,
         None => Type::Undefined,
@@ -193,7 +447,11 @@ fn analyze_declaration(declaration: Ast::Declarator, kind: Ast::DeclarationKind,
 
     variable_type.assign_name(&variable_name[..]);
 
-    let variable = Variable::new(variable_name, variable_type, VariableKind::from(kind));
+    let mut variable = Variable::new(variable_name, variable_type, VariableKind::from(kind));
+
+    if let Some(declared_type) = declared_type {
+        variable.set_declared_type(declared_type);
+    }
 
     Ok(variable)
 }
@@ -209,43 +467,3 @@ fn analyze_array_destructure(_elements: &[Ast::Pattern], _declaration: &Ast::Dec
 fn analyze_assignment_pattern(_pattern: &Ast::Pattern, _default: &Ast::Expression, _declaration: &Ast::Declarator, _kind: Ast::DeclarationKind) -> Result<Variable, Error> {
     panic!("Assignment Patterns are not implemented!");
 }
-
-fn get_line_from_offset(location: u32, content: &[&str]) -> (i32, i32) {
-    let mut counter = 0;
-    let mut line_number = 0;
-
-    for line in content {
-        // add 1 here to account for the new line byte
-        let line_lenght = (line.len() + 1) as u32;
-        let future_counter = counter + line_lenght;
-
-        if location >= future_counter {
-            line_number += 1;
-            counter = future_counter;
-
-            continue;
-        }
-
-        let byte_column = (location - counter) as u32;
-        // column starts at 1 not 0
-        let column = (&line[..byte_column as usize]).chars().count() as i32;
-
-        return (line_number, column);
-    }
-
-    (-1, -1)
-}
-
-fn get_line_from_offset_as_string(location: u32, content: &[&str]) -> String {
-    let (line, column) = get_line_from_offset(location, content);
-
-    line_column_as_string(line, column)
-}
-
-fn line_column_as_string(line: i32, column: i32) -> String {
-    if line < 0 || column < 0 {
-        return "out-of-bounds".to_string()
-    }
-
-    format!("{}:{}", line + 1, column + 1)
-}
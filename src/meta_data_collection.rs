@@ -1,3 +1,4 @@
+use arrays::element_type;
 use ast_nodes::{ExpressionNode, ExpressionNodeStruct, Node};
 use context::Context;
 use dynamic_typing::{
@@ -6,13 +7,14 @@ use dynamic_typing::{
 };
 use error::ValidationError;
 use expression_meta_data::{ComparisonMeta, ComparisonType, MetaCarry};
-use expressions::{determine_expression_type, expression_to_string};
+use expressions::determine_expression_type;
+use functions::callback_type_from;
 use ratel::ast as Ast;
 use std::clone::Clone;
 use std::sync::Arc;
 use traitcast::cast_ref;
 use traveler::AstEvent;
-use validation::map_argument_types;
+use validation::{call_return_type, callback_types, map_argument_types};
 
 pub fn collect_meta_data<'ast>(
     event: &AstEvent<'ast, ExpressionNodeStruct<'ast>>,
@@ -99,7 +101,10 @@ pub fn collect_meta_data<'ast>(
             context.set_node_meta_data(&node, new_mutex_ref(meta_data))
         }
 
-        AstEvent::Equality { node, left, right } => {
+        AstEvent::Equality { node, left, right }
+        | AstEvent::LooseEquality {
+            node, left, right, ..
+        } => {
             let mut meta_data = MetaCarry::new();
 
             meta_data.set_comparison(ComparisonMeta {
@@ -126,11 +131,7 @@ pub fn collect_meta_data<'ast>(
                 .node_meta_data(&alternate)
                 .borrow_safe(|data| data.expression_type());
 
-            let cond_type = if alternate_type != consequent_type {
-                Type::Mixed(vec![alternate_type, consequent_type])
-            } else {
-                alternate_type
-            };
+            let cond_type = Type::union(vec![alternate_type, consequent_type]);
 
             meta_data.set_expression_type(cond_type);
 
@@ -138,6 +139,7 @@ pub fn collect_meta_data<'ast>(
         }
 
         AstEvent::AlternateBody { expression } => context.node_meta_data(&expression),
+        AstEvent::Declaration { init, .. } => context.node_meta_data(&init),
         AstEvent::AfterIf { expression } => context.node_meta_data(&expression),
 
         AstEvent::ConsequentBody {
@@ -182,12 +184,27 @@ pub fn collect_meta_data<'ast>(
             context.set_node_meta_data(&node, new_mutex_ref(meta_data))
         }
 
-        AstEvent::DynamicPropertyAccess { node, property, .. } => {
+        AstEvent::DynamicPropertyAccess {
+            node,
+            object,
+            property,
+        } => {
+            let object_type = context
+                .node_meta_data(&object)
+                .borrow_safe(|data| data.expression_type());
             let property_type = context
                 .node_meta_data(&property)
                 .borrow_safe(|data| data.expression_type());
             let mut meta_data = MetaCarry::new();
 
+            // numeric indices into an array yield its elements
+            if let (Some(element_type), Type::Number) = (element_type(&object_type), &property_type)
+            {
+                meta_data.set_expression_type(element_type);
+
+                return context.set_node_meta_data(&node, new_mutex_ref(meta_data));
+            }
+
             if property_type != Type::String {
                 let error = ValidationError::InvalidType {
                     expression: property.source(),
@@ -199,7 +216,7 @@ pub fn collect_meta_data<'ast>(
                 let error = meta_data.set_error(Arc::from(error));
 
                 if let Some(error) = error {
-                    context.errors.insert(error);
+                    context.report(error);
                 }
             }
 
@@ -256,22 +273,22 @@ pub fn collect_meta_data<'ast>(
             arguments,
         } => {
             let mut meta_data = MetaCarry::new();
-            let function_definition = context
-                .scope
-                .locate(&expression_to_string(&function.expression))
-                .expect("function should exist");
             let arguments = map_argument_types(arguments, &context.data_map);
+            let return_type = call_return_type(node, function, &arguments, &context.scope);
 
-            let return_type = function_definition.borrow_safe(|definition| {
-                let def_type = definition.current_type();
+            meta_data.set_expression_type(return_type);
 
-                match def_type {
-                    Type::Function(data) => data.borrow_safe(|data| data.return_type(&arguments)),
-                    _ => panic!("only functions can be called!"),
-                }
-            });
+            context.set_node_meta_data(&node, new_mutex_ref(meta_data))
+        }
 
-            meta_data.set_expression_type(return_type);
+        AstEvent::New { node, .. } => {
+            let mut meta_data = MetaCarry::new();
+
+            // invalid constructors are reported by the validation pass
+            let instance_type = determine_expression_type(node.expression(), &context.scope)
+                .unwrap_or(Type::Undefined);
+
+            meta_data.set_expression_type(instance_type);
 
             context.set_node_meta_data(&node, new_mutex_ref(meta_data))
         }
@@ -297,14 +314,16 @@ pub fn collect_meta_data<'ast>(
 
             context.set_node_meta_data(&node, new_mutex_ref(meta_data))
         }
-        AstEvent::Function {
-            node,
-            params: _params,
-            body: _body,
-        } => {
+        AstEvent::Function { node, callback, .. } => {
             let mut meta_data = MetaCarry::new();
-            let fun_type = determine_expression_type(node.expression(), &context.scope)
-                .expect("expect function type");
+            let argument_types = callback_types(callback, &context.scope);
+            let fun_type = callback_type_from(
+                node.expression(),
+                node.location().start,
+                &argument_types,
+                &context.scope,
+            )
+            .expect("expect function type");
 
             meta_data.set_expression_type(fun_type);
 
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::path::Path;
+use ratel::ast as Ast;
+
+use dynamic_typing::{ Type, ObjectType, CustomType, Location, Scope, Scoped, ScopeRef, SafeBorrow, new_mutex_ref };
+use statics::commonjs_globals;
+use super::graph::ModuleGraph;
+
+// the scope a CommonJS module runs in, it holds `module`, `exports` and `require` and knows what
+// the `require` calls of the module evaluate to
+pub fn commonjs_scope(graph: &mut ModuleGraph, requires: &[(String, Location)], importer: &Path) -> ScopeRef {
+    let mut scope = Scope::new(String::from("CommonJS"), Some(graph.root_scope.clone()));
+
+    for global in commonjs_globals() {
+        scope.add(global);
+    }
+
+    // unresolved specifiers have already been reported with the other dependencies
+    for (specifier, location) in requires {
+        if let Ok(Some(module_type)) = graph.required_type(specifier, importer, location) {
+            scope.add_required_module(specifier.clone(), module_type);
+        }
+    }
+
+    new_mutex_ref(scope)
+}
+
+// `module.exports` after the module ran and the named exports it provides to ES modules. The
+// exports object is copied with its final properties, so other modules see every export no
+// matter where they read it
+pub fn commonjs_exports(scope: &ScopeRef) -> (Type, HashMap<String, Type>) {
+    let module_exports = scope.locate("module").ok()
+        .map(|module| module.borrow_safe(|module| module.current_type().clone()))
+        .and_then(|module| module.query_property("exports", &Location::latest()))
+        .unwrap_or(Type::Undefined);
+
+    let module_exports = match module_exports {
+        Type::Object(object) => Type::from(object.borrow_safe(|object| {
+            ObjectType::new(Some(object.name().to_owned()), object.settled_properties(), object.prototype().cloned())
+        })),
+
+        other => other,
+    };
+
+    let exports = object_exports(&module_exports);
+
+    (module_exports, exports)
+}
+
+// the names ES modules can import from a `module.exports` value, the value itself is the default
+pub fn object_exports(module_exports: &Type) -> HashMap<String, Type> {
+    let mut exports = match module_exports {
+        Type::Object(object) => object.borrow_safe(|object| object.properties.clone()),
+        _ => HashMap::new(),
+    };
+
+    exports.insert(String::from("default"), module_exports.clone());
+    exports
+}
+
+// `require("./x")` evaluates to the exports of the module it loads
+pub fn required_module_type(call_expression: &Ast::expression::CallExpression, scope: &ScopeRef) -> Option<Type> {
+    match call_expression.callee.item {
+        Ast::Expression::Identifier("require") => (),
+        _ => return None,
+    }
+
+    let argument = call_expression.arguments.iter().next()?;
+
+    match argument.item {
+        Ast::Expression::Literal(Ast::Literal::String(specifier)) => {
+            scope.required_module(specifier.trim_matches(|character| character == '"' || character == '\''))
+        },
+
+        _ => None,
+    }
+}
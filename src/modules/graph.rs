@@ -0,0 +1,288 @@
+use std::collections::{ HashMap, HashSet };
+use std::fs::{ self, File };
+use std::io::prelude::*;
+use std::path::{ Path, PathBuf };
+use std::sync::Arc;
+
+use dynamic_typing::{ Type, ObjectType, Variable, VariableKind, CustomTypeObject, Location, Scoped, ScopeRef, SafeBorrow };
+use error::{ ErrorVec, Diagnostics, ValidationError };
+use config::Config;
+use statics::OBJECT_PROTOTYPE;
+use declarations::load_declarations;
+use super::commonjs::object_exports;
+use super::resolution::{ Resolution, resolve, is_relative };
+use super::syntax::{ Import, ImportBinding, Export };
+use suggestions::Suggestions;
+
+// analyzes the source of the module at the given path, dependencies are loaded through the graph
+pub type Analyzer = fn(&mut ModuleGraph, &Path, String) -> ModuleRecord;
+
+#[derive(Clone)]
+pub struct ModuleRecord {
+    pub path: PathBuf,
+    pub source: String,
+    pub scope: ScopeRef,
+    pub exports: HashMap<String, Type>,
+
+    // the value of `module.exports`, only CommonJS modules have one
+    pub exports_object: Option<Type>,
+
+    // whether the source has import or export statements, the other sources are CommonJS modules
+    pub is_module: bool,
+    pub errors: Diagnostics,
+}
+
+impl ModuleRecord {
+    // a module that is only known through its type, like a package with a declaration file
+    pub fn declared(path: PathBuf, scope: ScopeRef, module_type: Type) -> Self {
+        let exports = object_exports(&module_type);
+
+        ModuleRecord { path, source: String::new(), scope, exports, exports_object: Some(module_type), is_module: false, errors: Diagnostics::new() }
+    }
+
+    // nothing is known about what the module exports, for example because it could not be parsed
+    pub fn is_opaque(&self) -> bool {
+        match self.exports_object {
+            Some(Type::Undefined) => true,
+            _ => false,
+        }
+    }
+}
+
+// every module reachable from the entry module, each one is analyzed once
+pub struct ModuleGraph {
+    pub root_scope: ScopeRef,
+    pub config: Arc<Config>,
+    analyzer: Analyzer,
+
+    // `declare module "name"` blocks of every declaration file loaded so far
+    declared_modules: HashMap<String, Type>,
+    modules: HashMap<PathBuf, Arc<ModuleRecord>>,
+    in_progress: HashSet<PathBuf>,
+    order: Vec<PathBuf>,
+}
+
+impl ModuleGraph {
+    pub fn new(root_scope: ScopeRef, declared_modules: HashMap<String, Type>, config: Arc<Config>, analyzer: Analyzer) -> Self {
+        ModuleGraph {
+            root_scope, config, analyzer, declared_modules,
+            modules: HashMap::new(), in_progress: HashSet::new(), order: vec!()
+        }
+    }
+
+    // modules in the order their analysis finished, dependencies come before their importers
+    pub fn modules(&self) -> Vec<Arc<ModuleRecord>> {
+        self.order.iter().map(|path| self.modules[path].clone()).collect()
+    }
+
+    // None if the module is still being analyzed further up, which happens for import cycles
+    pub fn load(&mut self, path: &Path) -> Option<Arc<ModuleRecord>> {
+        let path = fs::canonicalize(path).ok()?;
+
+        if let Some(module) = self.modules.get(&path) {
+            return Some(module.clone());
+        }
+
+        if self.in_progress.contains(&path) {
+            return None;
+        }
+
+        let mut source = String::new();
+
+        File::open(&path).and_then(|mut file| file.read_to_string(&mut source)).ok()?;
+
+        self.in_progress.insert(path.clone());
+
+        let analyzer = self.analyzer;
+        let module = Arc::new(analyzer(self, &path, source));
+
+        self.in_progress.remove(&path);
+        self.modules.insert(path.clone(), module.clone());
+        self.order.push(path);
+
+        Some(module)
+    }
+
+    // the exports of the module a specifier refers to. Ok(None) means the exports are not known,
+    // because the module is a package that can not be analyzed. Modules that are still being
+    // analyzed further up are cyclic imports
+    pub fn dependency(&mut self, specifier: &str, importer: &Path, location: &Location) -> Result<Option<HashMap<String, Type>>, ValidationError> {
+        let module = self.dependency_record(specifier, importer, location)?;
+
+        Ok(module.map(|module| module.exports.clone()))
+    }
+
+    // what `require(specifier)` evaluates to, ES modules are required as their namespace object
+    pub fn required_type(&mut self, specifier: &str, importer: &Path, location: &Location) -> Result<Option<Type>, ValidationError> {
+        let module = self.dependency_record(specifier, importer, location)?;
+
+        Ok(module.map(|module| match module.exports_object {
+            Some(ref exports_object) => exports_object.clone(),
+            None => namespace(specifier, &Some(module.exports.clone())),
+        }))
+    }
+
+    fn dependency_record(&mut self, specifier: &str, importer: &Path, location: &Location) -> Result<Option<Arc<ModuleRecord>>, ValidationError> {
+        let unresolved = || ValidationError::UnresolvedModule { specifier: specifier.to_owned(), location: location.clone() };
+
+        if let Some(module_type) = self.declared_modules.get(specifier) {
+            return Ok(Some(Arc::new(ModuleRecord::declared(PathBuf::from(specifier), self.root_scope.clone(), module_type.clone()))));
+        }
+
+        let module = match resolve(specifier, importer, &self.config.resolution_roots()) {
+            Some(Resolution::Source(path)) => {
+                if self.in_progress.contains(&path) {
+                    return Err(ValidationError::CyclicImport { specifier: specifier.to_owned(), location: location.clone() });
+                }
+
+                self.load(&path).ok_or_else(unresolved)?
+            },
+
+            Some(Resolution::Declarations(path)) => match self.load_declaration_file(&path) {
+                Some(module) => module,
+                None => return Ok(None),
+            },
+
+            // packages that are not installed are unknown, not wrong
+            None if is_relative(specifier) => return Err(unresolved()),
+            None => return Ok(None),
+        };
+
+        if module.is_opaque() {
+            return Ok(None);
+        }
+
+        Ok(Some(module))
+    }
+
+    // declaration files are cached with the modules, but they are not analyzed or reported
+    fn load_declaration_file(&mut self, path: &Path) -> Option<Arc<ModuleRecord>> {
+        if let Some(module) = self.modules.get(path) {
+            return Some(module.clone());
+        }
+
+        let mut source = String::new();
+
+        File::open(path).and_then(|mut file| file.read_to_string(&mut source)).ok()?;
+
+        let declarations = load_declarations(&source, &self.root_scope).ok()?;
+        let exports: HashMap<String, Type> = declarations.globals.iter()
+            .map(|global| (global.name().to_owned(), global.current_type().clone()))
+            .collect();
+
+        let module_type = declarations.exported
+            .unwrap_or_else(|| namespace(&path.to_string_lossy(), &Some(exports)));
+
+        self.declared_modules.extend(declarations.modules);
+
+        let module = Arc::new(ModuleRecord::declared(path.to_owned(), self.root_scope.clone(), module_type));
+
+        self.modules.insert(path.to_owned(), module.clone());
+
+        Some(module)
+    }
+
+    // the variables an import statement introduces, the module is resolved and names it does not
+    // export are reported
+    pub fn import_bindings(&mut self, import: &Import, importer: &Path, errors: &mut ErrorVec) -> Vec<Variable> {
+        let exports = match self.dependency(&import.specifier, importer, &import.location) {
+            Ok(exports) => exports,
+            Err(error) => {
+                errors.insert(Arc::new(error));
+                None
+            },
+        };
+
+        import.bindings.iter().map(|binding| {
+            let (local, binding_type) = match binding {
+                ImportBinding::Default(local) => (local, self.export(&exports, import, "default", errors)),
+                ImportBinding::Named { imported, local } => (local, self.export(&exports, import, imported, errors)),
+                ImportBinding::Namespace(local) => (local, namespace(local, &exports)),
+            };
+
+            Variable::new(local.clone(), binding_type, VariableKind::Const)
+        }).collect()
+    }
+
+    // the exports of a module once its own statements have been analyzed
+    pub fn module_exports(&mut self, exports: &[Export], declared_names: &HashMap<u32, Vec<String>>, scope: &ScopeRef, importer: &Path, errors: &mut ErrorVec) -> HashMap<String, Type> {
+        let mut module_exports = HashMap::new();
+        let local_type = |name: &str| {
+            scope.locate(name).ok().map(|variable| variable.borrow_safe(|variable| variable.current_type().clone()))
+        };
+
+        for export in exports {
+            match export {
+                Export::Local { local, exported } => {
+                    if let Some(local_type) = local_type(local) {
+                        module_exports.insert(exported.clone(), local_type);
+                    }
+                },
+
+                Export::Declaration { offset, is_default } => {
+                    for name in declared_names.get(offset).into_iter().flat_map(|names| names.iter()) {
+                        let exported = if *is_default { String::from("default") } else { name.clone() };
+
+                        if let Some(local_type) = local_type(name) {
+                            module_exports.insert(exported, local_type);
+                        }
+                    }
+                },
+
+                Export::Forwarded { specifier, imported, exported, location } => {
+                    let import = Import { specifier: specifier.clone(), bindings: vec!(), location: location.clone() };
+                    let dependency = self.dependency(specifier, importer, location).unwrap_or(None);
+                    let forwarded_type = self.export(&dependency, &import, imported, errors);
+
+                    module_exports.insert(exported.clone(), forwarded_type);
+                },
+
+                Export::ForwardedNamespace { specifier, exported, location } => {
+                    let dependency = self.dependency(specifier, importer, location).unwrap_or(None);
+
+                    module_exports.insert(exported.clone(), namespace(exported, &dependency));
+                },
+
+                // the default export is not part of `export *`
+                Export::ForwardedAll { specifier, location } => {
+                    if let Ok(Some(dependency)) = self.dependency(specifier, importer, location) {
+                        module_exports.extend(dependency.into_iter().filter(|(name, _)| name != "default"));
+                    }
+                },
+            }
+        }
+
+        module_exports
+    }
+
+    fn export(&self, exports: &Option<HashMap<String, Type>>, import: &Import, name: &str, errors: &mut ErrorVec) -> Type {
+        let exports = match exports {
+            Some(exports) => exports,
+            None => return Type::Undefined,
+        };
+
+        match exports.get(name) {
+            Some(export_type) => export_type.clone(),
+            None => {
+                errors.insert(Arc::new(ValidationError::UnknownProperty {
+                    object: import.specifier.clone(),
+                    property: name.to_owned(),
+                    suggestions: Suggestions::closest(name, exports.keys().cloned()),
+                    location: import.location.clone(),
+                }));
+
+                Type::Undefined
+            },
+        }
+    }
+}
+
+// `import * as name` binds an object holding every export of the module
+fn namespace(name: &str, exports: &Option<HashMap<String, Type>>) -> Type {
+    let properties = match exports {
+        Some(exports) => exports.clone(),
+        None => return Type::Undefined,
+    };
+
+    Type::from(ObjectType::new(Some(name.to_owned()), properties, Some(CustomTypeObject::from(&*OBJECT_PROTOTYPE))))
+}
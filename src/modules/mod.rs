@@ -0,0 +1,8 @@
+mod commonjs;
+mod graph;
+mod resolution;
+mod syntax;
+
+pub use self::commonjs::{ commonjs_scope, commonjs_exports, required_module_type };
+pub use self::graph::{ ModuleGraph, ModuleRecord };
+pub use self::syntax::extract_module_syntax;
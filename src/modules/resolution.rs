@@ -0,0 +1,221 @@
+use std::fs::{ self, File };
+use std::io::prelude::*;
+use std::path::{ Path, PathBuf };
+use serde_json::{ self, Value };
+
+// conditions of the package.json `exports` field in the order they are tried, declarations first
+const CONDITIONS: [&str; 6] = ["types", "import", "module", "require", "node", "default"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resolution {
+    Source(PathBuf),
+    Declarations(PathBuf),
+}
+
+pub fn is_relative(specifier: &str) -> bool {
+    specifier.starts_with("./") || specifier.starts_with("../") || specifier.starts_with('/')
+}
+
+// relative specifiers name files next to the importer, everything else is a package found by
+// walking up the node_modules directories like node does, or inside of one of the roots
+pub fn resolve(specifier: &str, importer: &Path, roots: &[PathBuf]) -> Option<Resolution> {
+    let directory = importer.parent().unwrap_or_else(|| Path::new(""));
+
+    if is_relative(specifier) {
+        return source_file(&directory.join(specifier)).map(Resolution::Source);
+    }
+
+    let (package_name, subpath) = split_specifier(specifier);
+
+    for directory in directory.ancestors() {
+        if directory.file_name().map(|name| name == "node_modules").unwrap_or(false) {
+            continue;
+        }
+
+        let package = directory.join("node_modules").join(package_name);
+
+        if package.is_dir() {
+            return resolve_in_package(&package, subpath);
+        }
+    }
+
+    for root in roots {
+        if let Some(resolution) = resolve_file(&root.join(specifier)) {
+            return Some(resolution);
+        }
+
+        let package = root.join(package_name);
+
+        if package.is_dir() {
+            return resolve_in_package(&package, subpath);
+        }
+    }
+
+    None
+}
+
+// `@scope/name/sub/path` is the package `@scope/name` and the path `sub/path` inside of it
+fn split_specifier(specifier: &str) -> (&str, Option<&str>) {
+    let name_segments = if specifier.starts_with('@') { 2 } else { 1 };
+    let name_end = specifier.match_indices('/').nth(name_segments - 1).map(|(index, _)| index);
+
+    match name_end {
+        Some(index) => (&specifier[..index], Some(&specifier[index + 1..])),
+        None => (specifier, None),
+    }
+}
+
+fn resolve_in_package(package: &Path, subpath: Option<&str>) -> Option<Resolution> {
+    let manifest = read_manifest(package);
+    let export_key = match subpath {
+        Some(subpath) => format!("./{}", subpath),
+        None => String::from("."),
+    };
+
+    if let Some(target) = export_target(&manifest, &export_key) {
+        for candidate in conditional_targets(target) {
+            if let Some(resolution) = resolve_file(&package.join(candidate)) {
+                return Some(resolution);
+            }
+        }
+    }
+
+    if let Some(subpath) = subpath {
+        return resolve_file(&package.join(subpath));
+    }
+
+    // a bundled declaration file wins over the code it describes
+    for field in &["types", "typings"] {
+        if let Some(types) = manifest.get(field).and_then(|types| types.as_str()) {
+            if let Some(declarations) = declaration_file(&package.join(types)) {
+                return Some(Resolution::Declarations(declarations));
+            }
+        }
+    }
+
+    let entry = manifest.get("module")
+        .or_else(|| manifest.get("main"))
+        .and_then(|entry| entry.as_str())
+        .unwrap_or("index.js");
+
+    resolve_file(&package.join(entry))
+        .or_else(|| resolve_file(&package.join("index.js")))
+}
+
+fn read_manifest(package: &Path) -> Value {
+    let mut source = String::new();
+
+    if File::open(package.join("package.json")).and_then(|mut file| file.read_to_string(&mut source)).is_err() {
+        return Value::Null;
+    }
+
+    serde_json::from_str(&source).unwrap_or(Value::Null)
+}
+
+// the `exports` entry for a subpath, a string or a conditions object directly under `exports`
+// is the entry of the package itself
+fn export_target<'a>(manifest: &'a Value, key: &str) -> Option<&'a Value> {
+    let exports = manifest.get("exports")?;
+
+    let is_subpath_map = exports.as_object()
+        .map(|entries| entries.keys().any(|entry| entry.starts_with('.')))
+        .unwrap_or(false);
+
+    if is_subpath_map {
+        return exports.get(key);
+    }
+
+    if key == "." {
+        return Some(exports);
+    }
+
+    None
+}
+
+fn conditional_targets(target: &Value) -> Vec<String> {
+    match target {
+        Value::String(path) => vec!(path.clone()),
+        Value::Array(alternatives) => alternatives.iter().flat_map(conditional_targets).collect(),
+
+        Value::Object(conditions) => CONDITIONS.iter()
+            .filter_map(|condition| conditions.get(*condition))
+            .flat_map(conditional_targets)
+            .collect(),
+
+        _ => vec!(),
+    }
+}
+
+// files inside of packages are typed by a declaration file next to them when there is one
+fn resolve_file(path: &Path) -> Option<Resolution> {
+    if let Some(declarations) = declaration_file(path) {
+        return Some(Resolution::Declarations(declarations));
+    }
+
+    source_file(path).map(Resolution::Source)
+}
+
+fn declaration_file(path: &Path) -> Option<PathBuf> {
+    let name = path.to_string_lossy();
+    let stem = ["js", "mjs", "cjs"].iter()
+        .find(|extension| path.extension().map(|actual| actual == **extension).unwrap_or(false))
+        .map(|extension| &name[..name.len() - extension.len() - 1])
+        .unwrap_or(&name);
+
+    let candidates = if name.ends_with(".d.ts") {
+        vec!(path.to_owned())
+    } else {
+        vec!(PathBuf::from(format!("{}.d.ts", stem)), path.join("index.d.ts"))
+    };
+
+    existing_file(candidates)
+}
+
+fn source_file(path: &Path) -> Option<PathBuf> {
+    existing_file(vec!(
+        path.to_owned(),
+        PathBuf::from(format!("{}.js", path.display())),
+        PathBuf::from(format!("{}.mjs", path.display())),
+        PathBuf::from(format!("{}.cjs", path.display())),
+        path.join("index.js"),
+    ))
+}
+
+fn existing_file(candidates: Vec<PathBuf>) -> Option<PathBuf> {
+    candidates.into_iter()
+        .find(|candidate| candidate.is_file())
+        .and_then(|candidate| fs::canonicalize(candidate).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(source: &str) -> Value {
+        serde_json::from_str(source).unwrap()
+    }
+
+    #[test]
+    fn tries_export_conditions_in_order() {
+        let manifest = manifest(r#"{ "exports": { "default": "./index.js", "require": "./index.cjs", "types": "./index.d.ts", "import": "./index.mjs" } }"#);
+        let target = export_target(&manifest, ".").unwrap();
+
+        assert_eq!(conditional_targets(target), vec!("./index.d.ts", "./index.mjs", "./index.cjs", "./index.js"));
+    }
+
+    #[test]
+    fn resolves_nested_conditions_and_subpaths() {
+        let manifest = manifest(r#"{ "exports": { ".": "./main.js", "./utils": { "node": { "import": "./utils.mjs", "require": "./utils.cjs" }, "default": "./utils.js" } } }"#);
+
+        assert_eq!(conditional_targets(export_target(&manifest, ".").unwrap()), vec!("./main.js"));
+        assert_eq!(conditional_targets(export_target(&manifest, "./utils").unwrap()), vec!("./utils.mjs", "./utils.cjs", "./utils.js"));
+        assert_eq!(export_target(&manifest, "./missing"), None);
+    }
+
+    #[test]
+    fn splits_scoped_specifiers() {
+        assert_eq!(split_specifier("lodash"), ("lodash", None));
+        assert_eq!(split_specifier("lodash/fp/map"), ("lodash", Some("fp/map")));
+        assert_eq!(split_specifier("@scope/name/sub"), ("@scope/name", Some("sub")));
+    }
+}
@@ -0,0 +1,526 @@
+use dynamic_typing::Location;
+
+// the binding an `export default <expression>` is rewritten to, `$` keeps it from clashing with
+// names of the module
+pub const DEFAULT_BINDING: &str = "$default";
+
+#[derive(Debug, Clone)]
+pub enum ImportBinding {
+    Default(String),
+    Named { imported: String, local: String },
+    Namespace(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub specifier: String,
+    pub bindings: Vec<ImportBinding>,
+    pub location: Location,
+}
+
+#[derive(Debug, Clone)]
+pub enum Export {
+    Local { local: String, exported: String },
+
+    // `export const a = 1` or `export default class A {}`, the names are known once the
+    // statement starting at offset has been parsed
+    Declaration { offset: u32, is_default: bool },
+
+    Forwarded { specifier: String, imported: String, exported: String, location: Location },
+    ForwardedNamespace { specifier: String, exported: String, location: Location },
+    ForwardedAll { specifier: String, location: Location },
+}
+
+// the import and export statements of a module and the source without them, which the
+// parser understands. Offsets in the remaining source are unchanged
+#[derive(Debug)]
+pub struct ModuleSyntax {
+    pub source: String,
+    pub imports: Vec<Import>,
+    pub exports: Vec<Export>,
+
+    // `require("...")` calls with a literal specifier, they stay in the source
+    pub requires: Vec<(String, Location)>,
+}
+
+impl ModuleSyntax {
+    // modules without any import or export statement are CommonJS modules
+    pub fn is_commonjs(&self) -> bool {
+        self.imports.is_empty() && self.exports.is_empty()
+    }
+
+    // the modules re-exports and `require` calls refer to, with the statement that refers to it.
+    // Imports are resolved when their bindings are created
+    pub fn forwarded_dependencies(&self) -> Vec<(String, Location)> {
+        let exports = self.exports.iter().filter_map(|export| match export {
+            Export::Forwarded { specifier, location, .. } => Some((specifier.clone(), location.clone())),
+            Export::ForwardedNamespace { specifier, location, .. } => Some((specifier.clone(), location.clone())),
+            Export::ForwardedAll { specifier, location } => Some((specifier.clone(), location.clone())),
+            _ => None,
+        });
+
+        exports.chain(self.requires.iter().cloned()).collect()
+    }
+}
+
+pub fn extract_module_syntax(source: &str) -> ModuleSyntax {
+    let characters: Vec<(usize, char)> = source.char_indices().collect();
+    let mut blanked: Vec<(usize, usize, String)> = vec!();
+    let mut syntax = ModuleSyntax { source: String::new(), imports: vec!(), exports: vec!(), requires: vec!() };
+    let mut depth = 0;
+    let mut statement_start = true;
+    let mut index = 0;
+
+    while index < characters.len() {
+        let (offset, character) = characters[index];
+        let next = characters.get(index + 1).map(|(_, character)| *character);
+
+        if character == '\n' {
+            statement_start = statement_start || depth == 0;
+            index += 1;
+            continue;
+        }
+
+        if character.is_whitespace() {
+            index += 1;
+            continue;
+        }
+
+        if character == '/' && (next == Some('/') || next == Some('*')) {
+            index = skip_comment(&characters, index);
+            continue;
+        }
+
+        if character == '"' || character == '\'' || character == '`' {
+            index = skip_string(&characters, index);
+            statement_start = false;
+            continue;
+        }
+
+        if is_identifier_start(character) {
+            let (word, end) = read_word(&characters, index);
+
+            if depth == 0 && statement_start && (word == "import" || word == "export") {
+                let mut cursor = Cursor { characters: &characters, index: end, source };
+                let statement = if word == "import" {
+                    import_statement(&mut cursor, offset, &mut syntax)
+                } else {
+                    export_statement(&mut cursor, offset, &mut syntax)
+                };
+
+                if let Some(replacement) = statement {
+                    let end_offset = cursor.offset();
+
+                    blanked.push((offset, end_offset, replacement));
+                    index = cursor.index;
+                    statement_start = true;
+                    continue;
+                }
+            }
+
+            if word == "require" && !is_property(&characters, index) {
+                let mut cursor = Cursor { characters: &characters, index: end, source };
+
+                if let Some(specifier) = required_specifier(&mut cursor) {
+                    syntax.requires.push((specifier, cursor.location_from(offset)));
+                }
+            }
+
+            index = end;
+            statement_start = false;
+            continue;
+        }
+
+        match character {
+            '{' | '(' | '[' => depth += 1,
+            '}' | ')' | ']' => depth -= 1,
+            _ => (),
+        }
+
+        statement_start = depth == 0 && (character == ';' || character == '}');
+        index += 1;
+    }
+
+    syntax.source = blank(source, &blanked);
+    syntax
+}
+
+// replaces the ranges with their replacement padded by spaces, line breaks are kept so lines
+// and columns of the remaining code do not move
+fn blank(source: &str, ranges: &[(usize, usize, String)]) -> String {
+    let mut output = String::with_capacity(source.len());
+    let mut position = 0;
+
+    for (start, end, replacement) in ranges {
+        output.push_str(&source[position..*start]);
+        output.push_str(replacement);
+
+        let mut written = replacement.len();
+
+        for character in source[*start..*end].chars().skip(replacement.chars().count()) {
+            if character == '\n' {
+                output.push('\n');
+                written += 1;
+                continue;
+            }
+
+            for _ in 0..character.len_utf8() {
+                output.push(' ');
+            }
+
+            written += character.len_utf8();
+        }
+
+        debug_assert_eq!(written, end - start);
+        position = *end;
+    }
+
+    output.push_str(&source[position..]);
+    output
+}
+
+// import "./a"; import a, { b as c } from "./a"; import * as a from "./a";
+fn import_statement(cursor: &mut Cursor, start: usize, syntax: &mut ModuleSyntax) -> Option<String> {
+    let mut bindings = vec!();
+
+    // dynamic imports and import.meta are expressions
+    if cursor.peek_is('(') || cursor.peek_is('.') {
+        return None;
+    }
+
+    if let Some(specifier) = cursor.string() {
+        let location = cursor.end_statement(start);
+
+        syntax.imports.push(Import { specifier, bindings, location });
+
+        return Some(String::new());
+    }
+
+    if let Some(name) = cursor.identifier() {
+        bindings.push(ImportBinding::Default(name));
+
+        if !cursor.eat(',') {
+            return finish_import(cursor, start, bindings, syntax);
+        }
+    }
+
+    if cursor.eat('*') {
+        if !cursor.keyword("as") {
+            return None;
+        }
+
+        bindings.push(ImportBinding::Namespace(cursor.identifier()?));
+    } else {
+        for (imported, local) in cursor.name_list()? {
+            bindings.push(ImportBinding::Named { imported, local });
+        }
+    }
+
+    finish_import(cursor, start, bindings, syntax)
+}
+
+fn finish_import(cursor: &mut Cursor, start: usize, bindings: Vec<ImportBinding>, syntax: &mut ModuleSyntax) -> Option<String> {
+    if !cursor.keyword("from") {
+        return None;
+    }
+
+    let specifier = cursor.string()?;
+
+    let location = cursor.end_statement(start);
+
+    syntax.imports.push(Import { specifier, bindings, location });
+
+    Some(String::new())
+}
+
+fn export_statement(cursor: &mut Cursor, start: usize, syntax: &mut ModuleSyntax) -> Option<String> {
+    if cursor.eat('*') {
+        let exported = if cursor.keyword("as") { Some(cursor.identifier()?) } else { None };
+
+        if !cursor.keyword("from") {
+            return None;
+        }
+
+        let specifier = cursor.string()?;
+
+        let location = cursor.end_statement(start);
+
+        syntax.exports.push(match exported {
+            Some(exported) => Export::ForwardedNamespace { specifier, exported, location },
+            None => Export::ForwardedAll { specifier, location },
+        });
+
+        return Some(String::new());
+    }
+
+    if cursor.peek_is('{') {
+        let names = cursor.name_list()?;
+
+        if cursor.keyword("from") {
+            let specifier = cursor.string()?;
+
+            let location = cursor.end_statement(start);
+
+            for (imported, exported) in names {
+                syntax.exports.push(Export::Forwarded { specifier: specifier.clone(), imported, exported, location: location.clone() });
+            }
+        } else {
+            cursor.eat(';');
+
+            for (local, exported) in names {
+                syntax.exports.push(Export::Local { local, exported });
+            }
+        }
+
+        return Some(String::new());
+    }
+
+    let is_default = cursor.keyword("default");
+
+    cursor.skip_trivia();
+
+    let declaration_start = cursor.index;
+    let declaration_offset = cursor.offset();
+    let keyword = cursor.identifier();
+    let is_declaration = match keyword.as_ref().map(|keyword| keyword.as_str()) {
+        Some("function") | Some("class") if is_default => cursor.skip_trivia() && cursor.peek_identifier(),
+        Some("const") | Some("let") | Some("var") | Some("function") | Some("class") => !is_default,
+        _ => false,
+    };
+
+    cursor.index = declaration_start;
+
+    // only the export keywords are removed, the declaration itself stays in place
+    if is_declaration {
+        syntax.exports.push(Export::Declaration { offset: declaration_offset as u32, is_default });
+
+        return Some(String::new());
+    }
+
+    if !is_default {
+        return None;
+    }
+
+    syntax.exports.push(Export::Local { local: DEFAULT_BINDING.to_owned(), exported: String::from("default") });
+
+    // `export default` is at least as long as its replacement
+    Some(format!("var {}=", DEFAULT_BINDING))
+}
+
+// require("./a") and require('./a'), other arguments can not be resolved up front
+fn required_specifier(cursor: &mut Cursor) -> Option<String> {
+    if !cursor.eat('(') {
+        return None;
+    }
+
+    let specifier = cursor.string()?;
+
+    if !cursor.eat(')') {
+        return None;
+    }
+
+    Some(specifier)
+}
+
+struct Cursor<'a> {
+    characters: &'a [(usize, char)],
+    index: usize,
+    source: &'a str,
+}
+
+impl<'a> Cursor<'a> {
+    fn offset(&self) -> usize {
+        self.characters.get(self.index).map(|(offset, _)| *offset).unwrap_or(self.source.len())
+    }
+
+    fn location_from(&self, start: usize) -> Location {
+        Location { start: start as u32, end: self.offset() as u32, line: 0, column: 0 }
+    }
+
+    // the location of the statement up to here, a trailing semicolon is consumed but not included
+    fn end_statement(&mut self, start: usize) -> Location {
+        let location = self.location_from(start);
+
+        self.eat(';');
+        location
+    }
+
+    // always true, so it can be chained into conditions
+    fn skip_trivia(&mut self) -> bool {
+        while let Some((_, character)) = self.characters.get(self.index) {
+            let next = self.characters.get(self.index + 1).map(|(_, character)| *character);
+
+            if *character == '/' && (next == Some('/') || next == Some('*')) {
+                self.index = skip_comment(self.characters, self.index);
+                continue;
+            }
+
+            if !character.is_whitespace() {
+                break;
+            }
+
+            self.index += 1;
+        }
+
+        true
+    }
+
+    fn peek_is(&mut self, expected: char) -> bool {
+        self.skip_trivia();
+        self.characters.get(self.index).map(|(_, character)| *character == expected).unwrap_or(false)
+    }
+
+    fn peek_identifier(&mut self) -> bool {
+        self.skip_trivia();
+        self.characters.get(self.index).map(|(_, character)| is_identifier_start(*character)).unwrap_or(false)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if !self.peek_is(expected) {
+            return false;
+        }
+
+        self.index += 1;
+        true
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        if !self.peek_identifier() {
+            return false;
+        }
+
+        let (word, end) = read_word(self.characters, self.index);
+
+        if word != keyword {
+            return false;
+        }
+
+        self.index = end;
+        true
+    }
+
+    fn identifier(&mut self) -> Option<String> {
+        if !self.peek_identifier() {
+            return None;
+        }
+
+        let (word, end) = read_word(self.characters, self.index);
+
+        self.index = end;
+        Some(word)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.skip_trivia();
+
+        let (start, quote) = *self.characters.get(self.index)?;
+
+        if quote != '"' && quote != '\'' {
+            return None;
+        }
+
+        let end = skip_string(self.characters, self.index);
+
+        if end < self.index + 2 || self.characters[end - 1].1 != quote {
+            return None;
+        }
+
+        let end_offset = self.characters.get(end).map(|(offset, _)| *offset).unwrap_or(self.source.len());
+
+        self.index = end;
+        Some(self.source[start + 1..end_offset - 1].to_owned())
+    }
+
+    // { a, b as c, "d" as e }, as pairs of the outer and the inner name
+    fn name_list(&mut self) -> Option<Vec<(String, String)>> {
+        let mut names = vec!();
+
+        if !self.eat('{') {
+            return None;
+        }
+
+        while !self.eat('}') {
+            let name = match self.identifier() {
+                Some(name) => name,
+                None => self.string()?,
+            };
+
+            let alias = if self.keyword("as") { self.identifier()? } else { name.clone() };
+
+            names.push((name, alias));
+
+            if !self.eat(',') && !self.peek_is('}') {
+                return None;
+            }
+        }
+
+        Some(names)
+    }
+}
+
+fn is_identifier_start(character: char) -> bool {
+    character.is_alphabetic() || character == '_' || character == '$'
+}
+
+// whether the word at index is accessed on an object, like `loader.require`
+fn is_property(characters: &[(usize, char)], index: usize) -> bool {
+    characters[..index].iter().rev()
+        .find(|(_, character)| !character.is_whitespace())
+        .map(|(_, character)| *character == '.')
+        .unwrap_or(false)
+}
+
+fn read_word(characters: &[(usize, char)], start: usize) -> (String, usize) {
+    let mut word = String::new();
+    let mut index = start;
+
+    while let Some((_, character)) = characters.get(index) {
+        if !(character.is_alphanumeric() || *character == '_' || *character == '$') {
+            break;
+        }
+
+        word.push(*character);
+        index += 1;
+    }
+
+    (word, index)
+}
+
+fn skip_comment(characters: &[(usize, char)], start: usize) -> usize {
+    let mut index = start + 2;
+
+    if characters[start + 1].1 == '/' {
+        while index < characters.len() && characters[index].1 != '\n' {
+            index += 1;
+        }
+
+        return index;
+    }
+
+    while index < characters.len() && !(characters[index].1 == '*' && characters.get(index + 1).map(|(_, character)| *character) == Some('/')) {
+        index += 1;
+    }
+
+    index + 2
+}
+
+// returns the index after the closing quote, template literals are skipped as a whole
+fn skip_string(characters: &[(usize, char)], start: usize) -> usize {
+    let quote = characters[start].1;
+    let mut index = start + 1;
+
+    while let Some((_, character)) = characters.get(index) {
+        if *character == '\\' {
+            index += 2;
+            continue;
+        }
+
+        index += 1;
+
+        if *character == quote {
+            break;
+        }
+    }
+
+    index.min(characters.len())
+}
@@ -1,33 +1,35 @@
 use std::collections::HashMap;
 use failure::*;
-use dynamic_typing::{ ScopeRef, new_mutex_ref, ObjectType, CustomTypeObject, Type, FunctionType };
+use dynamic_typing::{ ScopeRef, new_mutex_ref, ObjectType, CustomTypeObject, Type, FunctionKind, Location, SafeBorrow };
+use functions::function_type_from;
 use ratel::ast as Ast;
 use expressions::{ expression_to_string, determine_expression_type };
 use statics::OBJECT_PROTOTYPE;
 use error::AccessError;
 
 pub fn type_from_properties(properties: &[Ast::Property], scope: &ScopeRef) -> Result<Type, Error> {
-    let properties: Result<HashMap<String, Type>, Error> = properties.iter().map(|property| {
-        let transformed = match property {
+    let mut own_properties = HashMap::new();
+    let mut methods = vec!();
+
+    for property in properties {
+        match property {
             Ast::Property::Literal { key, value } => {
-                (property_to_string(&key.item), determine_expression_type(&value, scope)?)
+                own_properties.insert(property_to_string(&key.item), determine_expression_type(&value, scope)?);
             },
 
             Ast::Property::Shorthand(property) => {
-                ((*property).to_string(), determine_expression_type(&Ast::Expression::Identifier(property), scope)?)
+                own_properties.insert((*property).to_string(), determine_expression_type(&Ast::Expression::Identifier(property), scope)?);
             },
 
-            Ast::Property::Method { key, value: _value } => {
-                (property_to_string(&key.item), Type::from(FunctionType::new(vec!())))
+            Ast::Property::Method { key, value } => {
+                methods.push((property_to_string(&key.item), key.start, value.item));
             },
 
             Ast::Property::Spread { argument } => panic!("Property spread for Object literals is not implement!, {:#?}", argument),
         };
+    }
 
-        Ok(transformed)
-    }).collect();
-
-    let mut properties = properties?;
+    let mut properties = own_properties;
     let prototype: Option<CustomTypeObject> = {
         let prototype = properties.get("__proto__");
 
@@ -46,26 +48,25 @@ pub fn type_from_properties(properties: &[Ast::Property], scope: &ScopeRef) -> R
 
     properties.remove("__proto__");
 
-    let new_type = Type::Object(new_mutex_ref(ObjectType::new(None, properties, prototype)));
+    let object_type = new_mutex_ref(ObjectType::new(None, properties, prototype));
+    let new_type = Type::from(&object_type);
+
+    // methods are typed once the object exists, because it is their this
+    for (name, start, method) in methods {
+        let Ast::Function { params, body, .. } = method;
+        let mut method_type = function_type_from(Some(name.clone()), &params, &body, Some(new_type.clone()), start, scope)?;
+
+        method_type.set_kind(FunctionKind::Method);
+
+        object_type.borrow_mut_safe(|object_type| object_type.properties.insert(name, Type::from(method_type)));
+    }
 
     Ok(new_type)
 }
 
 pub fn determine_member_type(expression: &Ast::Expression, property: Ast::Node<'_, &str>, scope: &ScopeRef) -> Result<Type, Error> {
     let object = determine_expression_type(expression, scope)?;
-    let member_type = object.properties(|properties| {
-        let mut member_type: Option<Type> = None;
-
-        for (name, type_) in properties {
-            if name != property.item {
-                continue;
-            }
-
-            member_type = Some(type_.clone());
-        }
-
-        member_type
-    });
+    let member_type = object.query_property(property.item, &Location::from(*property));
 
     if let Some(type_) = member_type {
         Ok(type_)
@@ -74,7 +75,7 @@ pub fn determine_member_type(expression: &Ast::Expression, property: Ast::Node<'
     }
 }
 
-fn property_to_string(property_key: &Ast::PropertyKey) -> String {
+pub fn property_to_string(property_key: &Ast::PropertyKey) -> String {
     match property_key {
         Ast::PropertyKey::Literal (value) => (*value).to_string(),
         Ast::PropertyKey::Binary (value) => (*value).to_string(),
@@ -0,0 +1,260 @@
+use std::iter::repeat;
+use std::sync::Arc;
+use serde_json;
+
+use config::Severity;
+use error::ValidationError;
+use fixes::{ Fix, fix_for };
+use modules::ModuleRecord;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Lsp,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            "lsp" => Some(OutputFormat::Lsp),
+            _ => None,
+        }
+    }
+}
+
+// a reported error with its zero based line and column positions in the module source
+struct Diagnostic {
+    error: Arc<ValidationError>,
+    severity: Severity,
+    start: (i32, i32),
+    end: (i32, i32),
+    fix: Option<Fix>,
+}
+
+#[derive(Serialize)]
+struct JsonPosition {
+    line: i32,
+    column: i32,
+}
+
+#[derive(Serialize)]
+struct JsonDiagnostic {
+    file: String,
+    code: &'static str,
+    rule: &'static str,
+    severity: Severity,
+    message: String,
+    start: JsonPosition,
+    end: JsonPosition,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    suggestions: Vec<String>,
+    fix: Option<Fix>,
+}
+
+#[derive(Serialize)]
+struct LspPosition {
+    line: i32,
+    character: i32,
+}
+
+#[derive(Serialize)]
+struct LspRange {
+    start: LspPosition,
+    end: LspPosition,
+}
+
+#[derive(Serialize)]
+struct LspDiagnostic {
+    range: LspRange,
+    severity: u8,
+    code: &'static str,
+    source: &'static str,
+    message: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<LspData>,
+}
+
+// what code actions of the client are built from
+#[derive(Serialize)]
+struct LspData {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    suggestions: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix: Option<Fix>,
+}
+
+#[derive(Serialize)]
+struct LspPublishParams {
+    uri: String,
+    diagnostics: Vec<LspDiagnostic>,
+}
+
+#[derive(Serialize)]
+struct LspNotification {
+    jsonrpc: &'static str,
+    method: &'static str,
+    params: LspPublishParams,
+}
+
+// prints the diagnostics of the modules, the result tells if any of them is an error
+pub fn report_diagnostics(modules: &[Arc<ModuleRecord>], format: OutputFormat) -> bool {
+    match format {
+        OutputFormat::Text => modules.iter().for_each(|module| print_text(module)),
+        OutputFormat::Json => print_json(modules),
+        OutputFormat::Lsp => modules.iter().for_each(|module| print_lsp(module)),
+    }
+
+    modules.iter().any(|module| module.errors.values().any(|severity| *severity == Severity::Error))
+}
+
+fn module_diagnostics(module: &ModuleRecord, content: &[&str]) -> Vec<Diagnostic> {
+    let mut errors: Vec<(&Arc<ValidationError>, &Severity)> = module.errors.iter().collect();
+
+    errors.sort_by_key(|(error, _)| error.location().start);
+
+    errors.into_iter().map(|(error, severity)| Diagnostic {
+        error: error.clone(),
+        severity: *severity,
+        start: get_line_from_offset(error.location().start, content),
+        end: get_line_from_offset(error.location().end, content),
+        fix: fix_for(error, &module.source),
+    }).collect()
+}
+
+fn print_text(module: &ModuleRecord) {
+    let structured_content: Vec<&str> = module.source.split('\n').collect();
+    let diagnostics = module_diagnostics(module, &structured_content);
+
+    println!("{}: {}", module.path.display(), diagnostics.len());
+
+    for diagnostic in diagnostics {
+        let (line_start, column_start) = diagnostic.start;
+        let (_, column_end) = diagnostic.end;
+        let line_content = structured_content.get(line_start as usize).cloned().unwrap_or("");
+        let mut range = column_end - column_start;
+
+        if range < 0 {
+            range = column_end;
+        }
+
+        let padding = repeat(" ").take(column_start.max(0) as usize).collect::<String>();
+        let locator = repeat("^").take(range.max(0) as usize).collect::<String>();
+
+        let label = match diagnostic.severity {
+            Severity::Warning => "Validation Warning",
+            Severity::Info => "Validation Info",
+            _ => "Validation Error",
+        };
+
+        println!("{} {}: {} at {}", label, diagnostic.error.code(), diagnostic.error, line_column_as_string(line_start, column_start));
+        println!("{}\n{}{}", line_content, padding, locator);
+
+        if let Some(fix) = diagnostic.fix {
+            println!("Fix: {}", fix.description);
+        }
+    }
+}
+
+// a single array holding the diagnostics of every module, lines and columns start at 1
+fn print_json(modules: &[Arc<ModuleRecord>]) {
+    let mut output = vec!();
+
+    for module in modules {
+        let structured_content: Vec<&str> = module.source.split('\n').collect();
+
+        for diagnostic in module_diagnostics(module, &structured_content) {
+            output.push(JsonDiagnostic {
+                file: module.path.display().to_string(),
+                code: diagnostic.error.code(),
+                rule: diagnostic.error.name(),
+                severity: diagnostic.severity,
+                message: diagnostic.error.to_string(),
+                start: JsonPosition { line: diagnostic.start.0 + 1, column: diagnostic.start.1 + 1 },
+                end: JsonPosition { line: diagnostic.end.0 + 1, column: diagnostic.end.1 + 1 },
+                suggestions: diagnostic.error.suggestions().to_vec(),
+                fix: diagnostic.fix,
+            });
+        }
+    }
+
+    println!("{}", serde_json::to_string_pretty(&output).unwrap());
+}
+
+// one publishDiagnostics notification per module, framed like messages of a language server.
+// Modules without diagnostics are published too, so clients clear what they showed before
+fn print_lsp(module: &ModuleRecord) {
+    let structured_content: Vec<&str> = module.source.split('\n').collect();
+    let position = |(line, column): (i32, i32)| LspPosition { line: line.max(0), character: column.max(0) };
+
+    let diagnostics = module_diagnostics(module, &structured_content).into_iter().map(|diagnostic| {
+        let suggestions = diagnostic.error.suggestions().to_vec();
+        let data = if suggestions.is_empty() && diagnostic.fix.is_none() {
+            None
+        } else {
+            Some(LspData { suggestions, fix: diagnostic.fix })
+        };
+
+        LspDiagnostic {
+            range: LspRange { start: position(diagnostic.start), end: position(diagnostic.end) },
+            severity: match diagnostic.severity {
+                Severity::Error => 1,
+                Severity::Warning => 2,
+                _ => 3,
+            },
+            code: diagnostic.error.code(),
+            source: "rusty",
+            message: diagnostic.error.to_string(),
+            data,
+        }
+    }).collect();
+
+    let notification = LspNotification {
+        jsonrpc: "2.0",
+        method: "textDocument/publishDiagnostics",
+        params: LspPublishParams { uri: format!("file://{}", module.path.display()), diagnostics },
+    };
+
+    let message = serde_json::to_string(&notification).unwrap();
+
+    print!("Content-Length: {}\r\n\r\n{}", message.len(), message);
+}
+
+fn get_line_from_offset(location: u32, content: &[&str]) -> (i32, i32) {
+    let mut counter = 0;
+    let mut line_number = 0;
+
+    for line in content {
+        // add 1 here to account for the new line byte
+        let line_lenght = (line.len() + 1) as u32;
+        let future_counter = counter + line_lenght;
+
+        if location >= future_counter {
+            line_number += 1;
+            counter = future_counter;
+
+            continue;
+        }
+
+        let byte_column = (location - counter) as u32;
+        // column starts at 1 not 0
+        let column = (&line[..byte_column as usize]).chars().count() as i32;
+
+        return (line_number, column);
+    }
+
+    (-1, -1)
+}
+
+fn line_column_as_string(line: i32, column: i32) -> String {
+    if line < 0 || column < 0 {
+        return "out-of-bounds".to_string()
+    }
+
+    format!("{}:{}", line + 1, column + 1)
+}
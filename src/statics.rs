@@ -1,53 +1,579 @@
 use std::collections::HashMap;
 use std::iter::FromIterator;
 
-use dynamic_typing::{ Type, FunctionType, ObjectType, CustomType, MutexRef, new_mutex_ref,
-    SafeBorrow, CustomTypeObject };
+use dynamic_typing::{ Type, FunctionType, FunctionKind, ObjectType, CustomType, MutexRef, new_mutex_ref,
+    SafeBorrow, CustomTypeObject, Variable, VariableKind };
 
 lazy_static! {
-    pub static ref STRING_PROTOTYPE: MutexRef<FunctionType> = {
-        let func_type = new_mutex_ref(FunctionType::new(vec!()));
+    pub static ref OBJECT_PROTOTYPE: MutexRef<ObjectType> = {
+        let properties = HashMap::from_iter(vec!(
+            method("hasOwnProperty", vec!(("property", Type::String)), Type::Boolean),
+            method("isPrototypeOf", vec!(("object", Type::Undefined)), Type::Boolean),
+            method("propertyIsEnumerable", vec!(("property", Type::String)), Type::Boolean),
+            method("toLocaleString", vec!(), Type::String),
+            method("toString", vec!(), Type::String),
+            method("valueOf", vec!(), Type::Undefined),
+        ));
 
-        func_type.borrow_mut_safe(|func_type| func_type.assign_name("String".to_owned()));
+        let object_type = ObjectType::new(Some(String::from("ObjectPrototype")), properties, None);
 
-        func_type
+        new_mutex_ref(object_type)
     };
 }
 
 lazy_static! {
-    pub static ref OBJECT_PROTOTYPE: MutexRef<ObjectType> = {
-        let properties = HashMap::from_iter(vec!(
-            (String::from("name"), Type::String)
-        ));
+    pub static ref FUNCTION_PROTOTYPE: MutexRef<ObjectType> = {
+        prototype_object("FunctionPrototype", vec!(
+            property("name", Type::String),
+            property("length", Type::Number),
+            method("apply", vec!(("thisArg", Type::Undefined), ("arguments", Type::Undefined)), Type::Undefined),
+            method("bind", vec!(("thisArg", Type::Undefined)), Type::Undefined),
+            method("call", vec!(("thisArg", Type::Undefined)), Type::Undefined),
+            method("toString", vec!(), Type::String),
+        ))
+    };
+}
 
-        let object_type = ObjectType::new(Some(String::from("ObjectPrototype")), properties, None);
+lazy_static! {
+    pub static ref STRING_PROTOTYPE: MutexRef<ObjectType> = {
+        let string_array = || Type::Composed { outer: (*ARRAY_PROTOTYPE).clone(), inner: Box::new(Type::String) };
 
-        new_mutex_ref(object_type)
+        prototype_object("StringPrototype", vec!(
+            property("length", Type::Number),
+            method("charAt", vec!(("index", Type::Number)), Type::String),
+            method("charCodeAt", vec!(("index", Type::Number)), Type::Number),
+            method("codePointAt", vec!(("index", Type::Number)), Type::Number),
+            method("concat", vec!(("string", Type::String)), Type::String),
+            method("endsWith", vec!(("search", Type::String), ("length", Type::Number)), Type::Boolean),
+            method("includes", vec!(("search", Type::String), ("position", Type::Number)), Type::Boolean),
+            method("indexOf", vec!(("search", Type::String), ("position", Type::Number)), Type::Number),
+            method("lastIndexOf", vec!(("search", Type::String), ("position", Type::Number)), Type::Number),
+            method("localeCompare", vec!(("compare", Type::String)), Type::Number),
+            method("match", vec!(("pattern", Type::RegExp)), Type::union(vec!(string_array(), Type::Null))),
+            method("normalize", vec!(("form", Type::String)), Type::String),
+            method("padEnd", vec!(("length", Type::Number), ("fill", Type::String)), Type::String),
+            method("padStart", vec!(("length", Type::Number), ("fill", Type::String)), Type::String),
+            method("repeat", vec!(("count", Type::Number)), Type::String),
+            method("replace", vec!(("pattern", Type::Undefined), ("replacement", Type::Undefined)), Type::String),
+            method("search", vec!(("pattern", Type::RegExp)), Type::Number),
+            method("slice", vec!(("start", Type::Number), ("end", Type::Number)), Type::String),
+            method("split", vec!(("separator", Type::Undefined), ("limit", Type::Number)), string_array()),
+            method("startsWith", vec!(("search", Type::String), ("position", Type::Number)), Type::Boolean),
+            method("substr", vec!(("start", Type::Number), ("length", Type::Number)), Type::String),
+            method("substring", vec!(("start", Type::Number), ("end", Type::Number)), Type::String),
+            method("toLocaleLowerCase", vec!(), Type::String),
+            method("toLocaleUpperCase", vec!(), Type::String),
+            method("toLowerCase", vec!(), Type::String),
+            method("toString", vec!(), Type::String),
+            method("toUpperCase", vec!(), Type::String),
+            method("trim", vec!(), Type::String),
+            method("trimEnd", vec!(), Type::String),
+            method("trimStart", vec!(), Type::String),
+            method("valueOf", vec!(), Type::String),
+        ))
+    };
+}
+
+lazy_static! {
+    pub static ref NUMBER_PROTOTYPE: MutexRef<ObjectType> = {
+        prototype_object("NumberPrototype", vec!(
+            method("toExponential", vec!(("digits", Type::Number)), Type::String),
+            method("toFixed", vec!(("digits", Type::Number)), Type::String),
+            method("toLocaleString", vec!(("locales", Type::Undefined), ("options", Type::Undefined)), Type::String),
+            method("toPrecision", vec!(("precision", Type::Number)), Type::String),
+            method("toString", vec!(("radix", Type::Number)), Type::String),
+            method("valueOf", vec!(), Type::Number),
+        ))
+    };
+}
+
+lazy_static! {
+    pub static ref BOOLEAN_PROTOTYPE: MutexRef<ObjectType> = {
+        prototype_object("BooleanPrototype", vec!(
+            method("toString", vec!(), Type::String),
+            method("valueOf", vec!(), Type::Boolean),
+        ))
+    };
+}
+
+lazy_static! {
+    pub static ref REGEXP_PROTOTYPE: MutexRef<ObjectType> = {
+        let string_array = Type::Composed { outer: (*ARRAY_PROTOTYPE).clone(), inner: Box::new(Type::String) };
+
+        prototype_object("RegExpPrototype", vec!(
+            property("flags", Type::String),
+            property("global", Type::Boolean),
+            property("ignoreCase", Type::Boolean),
+            property("lastIndex", Type::Number),
+            property("multiline", Type::Boolean),
+            property("source", Type::String),
+            property("sticky", Type::Boolean),
+            property("unicode", Type::Boolean),
+            method("exec", vec!(("string", Type::String)), Type::union(vec!(string_array, Type::Null))),
+            method("test", vec!(("string", Type::String)), Type::Boolean),
+            method("toString", vec!(), Type::String),
+        ))
     };
 }
 
 lazy_static! {
     pub static ref ARRAY_PROTOTYPE: MutexRef<ObjectType> = {
-        new_mutex_ref(ObjectType::new(
+        let prototype = new_mutex_ref(ObjectType::new_array(
             Some(String::from("ArrayPrototype")),
             HashMap::from_iter(vec!(
-                (String::from("length"), Type::Number)
+                property("length", Type::Number),
+                method("every", vec!(("callback", Type::Undefined)), Type::Boolean),
+                method("find", vec!(("callback", Type::Undefined)), Type::Undefined),
+                method("findIndex", vec!(("callback", Type::Undefined)), Type::Number),
+                method("forEach", vec!(("callback", Type::Undefined)), Type::Undefined),
+                method("includes", vec!(("element", Type::Undefined)), Type::Boolean),
+                method("indexOf", vec!(("element", Type::Undefined)), Type::Number),
+                method("join", vec!(("separator", Type::String)), Type::String),
+                method("lastIndexOf", vec!(("element", Type::Undefined)), Type::Number),
+                method("pop", vec!(), Type::Undefined),
+                method("push", vec!(("element", Type::Undefined)), Type::Number),
+                method("reduce", vec!(("callback", Type::Undefined), ("initial", Type::Undefined)), Type::Undefined),
+                method("reduceRight", vec!(("callback", Type::Undefined), ("initial", Type::Undefined)), Type::Undefined),
+                method("shift", vec!(), Type::Undefined),
+                method("some", vec!(("callback", Type::Undefined)), Type::Boolean),
+                method("toString", vec!(), Type::String),
+                method("unshift", vec!(("element", Type::Undefined)), Type::Number),
             )),
             Some(CustomTypeObject::from(&*OBJECT_PROTOTYPE))
+        ));
+
+        // these methods return arrays again, so they can only be added once the prototype exists
+        let array = Type::Composed { outer: prototype.clone(), inner: Box::new(Type::Undefined) };
+
+        prototype.borrow_mut_safe(|prototype| prototype.properties.extend(vec!(
+            method("concat", vec!(("array", array.clone())), array.clone()),
+            method("fill", vec!(("value", Type::Undefined)), array.clone()),
+            method("filter", vec!(("callback", Type::Undefined)), array.clone()),
+            method("flat", vec!(("depth", Type::Number)), array.clone()),
+            method("flatMap", vec!(("callback", Type::Undefined)), array.clone()),
+            method("map", vec!(("callback", Type::Undefined)), array.clone()),
+            method("reverse", vec!(), array.clone()),
+            method("slice", vec!(("start", Type::Number), ("end", Type::Number)), array.clone()),
+            method("sort", vec!(("compare", Type::Undefined)), array.clone()),
+            method("splice", vec!(("start", Type::Number), ("count", Type::Number)), array.clone()),
+        )));
+
+        prototype
+    };
+}
+
+lazy_static! {
+    pub static ref DATE_PROTOTYPE: MutexRef<ObjectType> = {
+        prototype_object("DatePrototype", vec!(
+            method("getDate", vec!(), Type::Number),
+            method("getDay", vec!(), Type::Number),
+            method("getFullYear", vec!(), Type::Number),
+            method("getHours", vec!(), Type::Number),
+            method("getMilliseconds", vec!(), Type::Number),
+            method("getMinutes", vec!(), Type::Number),
+            method("getMonth", vec!(), Type::Number),
+            method("getSeconds", vec!(), Type::Number),
+            method("getTime", vec!(), Type::Number),
+            method("getTimezoneOffset", vec!(), Type::Number),
+            method("getUTCDate", vec!(), Type::Number),
+            method("getUTCDay", vec!(), Type::Number),
+            method("getUTCFullYear", vec!(), Type::Number),
+            method("getUTCHours", vec!(), Type::Number),
+            method("getUTCMinutes", vec!(), Type::Number),
+            method("getUTCMonth", vec!(), Type::Number),
+            method("getUTCSeconds", vec!(), Type::Number),
+            method("setDate", vec!(("date", Type::Number)), Type::Number),
+            method("setFullYear", vec!(("year", Type::Number)), Type::Number),
+            method("setHours", vec!(("hours", Type::Number)), Type::Number),
+            method("setMilliseconds", vec!(("milliseconds", Type::Number)), Type::Number),
+            method("setMinutes", vec!(("minutes", Type::Number)), Type::Number),
+            method("setMonth", vec!(("month", Type::Number)), Type::Number),
+            method("setSeconds", vec!(("seconds", Type::Number)), Type::Number),
+            method("setTime", vec!(("time", Type::Number)), Type::Number),
+            method("toDateString", vec!(), Type::String),
+            method("toISOString", vec!(), Type::String),
+            method("toJSON", vec!(), Type::String),
+            method("toLocaleDateString", vec!(), Type::String),
+            method("toLocaleString", vec!(), Type::String),
+            method("toLocaleTimeString", vec!(), Type::String),
+            method("toString", vec!(), Type::String),
+            method("toTimeString", vec!(), Type::String),
+            method("toUTCString", vec!(), Type::String),
+            method("valueOf", vec!(), Type::Number),
         ))
     };
 }
 
 lazy_static! {
-    pub static ref OBJECT: Type = {
-        let mut func_type = FunctionType::new(vec!());
+    pub static ref MAP_PROTOTYPE: MutexRef<ObjectType> = {
+        let prototype = prototype_object("MapPrototype", vec!(
+            property("size", Type::Number),
+            method("clear", vec!(), Type::Undefined),
+            method("delete", vec!(("key", Type::Undefined)), Type::Boolean),
+            method("entries", vec!(), Type::Undefined),
+            method("forEach", vec!(("callback", Type::Undefined)), Type::Undefined),
+            method("get", vec!(("key", Type::Undefined)), Type::Undefined),
+            method("has", vec!(("key", Type::Undefined)), Type::Boolean),
+            method("keys", vec!(), Type::Undefined),
+            method("values", vec!(), Type::Undefined),
+        ));
+
+        let map = instance("Map", &prototype);
+
+        prototype.borrow_mut_safe(|prototype| prototype.properties.extend(vec!(
+            method("set", vec!(("key", Type::Undefined), ("value", Type::Undefined)), map),
+        )));
 
-        func_type.properties = HashMap::from_iter(vec!(
-            (String::from("prototype"), (Type::from(&*OBJECT_PROTOTYPE)))
+        prototype
+    };
+}
+
+lazy_static! {
+    pub static ref SET_PROTOTYPE: MutexRef<ObjectType> = {
+        let prototype = prototype_object("SetPrototype", vec!(
+            property("size", Type::Number),
+            method("clear", vec!(), Type::Undefined),
+            method("delete", vec!(("value", Type::Undefined)), Type::Boolean),
+            method("entries", vec!(), Type::Undefined),
+            method("forEach", vec!(("callback", Type::Undefined)), Type::Undefined),
+            method("has", vec!(("value", Type::Undefined)), Type::Boolean),
+            method("keys", vec!(), Type::Undefined),
+            method("values", vec!(), Type::Undefined),
         ));
 
-        func_type.assign_name("Object".to_owned());
+        let set = instance("Set", &prototype);
+
+        prototype.borrow_mut_safe(|prototype| prototype.properties.extend(vec!(
+            method("add", vec!(("value", Type::Undefined)), set),
+        )));
+
+        prototype
+    };
+}
+
+lazy_static! {
+    pub static ref PROMISE_PROTOTYPE: MutexRef<ObjectType> = {
+        let prototype = prototype_object("PromisePrototype", vec!());
+        let promise = instance("Promise", &prototype);
+
+        prototype.borrow_mut_safe(|prototype| prototype.properties.extend(vec!(
+            method("catch", vec!(("onRejected", Type::Undefined)), promise.clone()),
+            method("finally", vec!(("onFinally", Type::Undefined)), promise.clone()),
+            method("then", vec!(("onFulfilled", Type::Undefined), ("onRejected", Type::Undefined)), promise),
+        )));
+
+        prototype
+    };
+}
+
+lazy_static! {
+    pub static ref ERROR_PROTOTYPE: MutexRef<ObjectType> = {
+        prototype_object("ErrorPrototype", vec!(
+            property("message", Type::String),
+            property("name", Type::String),
+            property("stack", Type::String),
+            method("toString", vec!(), Type::String),
+        ))
+    };
+}
+
+lazy_static! {
+    pub static ref OBJECT: Type = {
+        let string_array = Type::Composed { outer: (*ARRAY_PROTOTYPE).clone(), inner: Box::new(Type::String) };
+        let array = Type::Composed { outer: (*ARRAY_PROTOTYPE).clone(), inner: Box::new(Type::Undefined) };
 
-        Type::Function(new_mutex_ref(func_type))
+        constructor("Object", &*OBJECT_PROTOTYPE, vec!(("value", Type::Undefined)), Type::Undefined, vec!(
+            method("assign", vec!(("target", Type::Undefined), ("source", Type::Undefined)), Type::Undefined),
+            method("create", vec!(("prototype", Type::Undefined), ("properties", Type::Undefined)), Type::Undefined),
+            method("defineProperty", vec!(("object", Type::Undefined), ("property", Type::String), ("descriptor", Type::Undefined)), Type::Undefined),
+            method("entries", vec!(("object", Type::Undefined)), array.clone()),
+            method("freeze", vec!(("object", Type::Undefined)), Type::Undefined),
+            method("getOwnPropertyNames", vec!(("object", Type::Undefined)), string_array.clone()),
+            method("getPrototypeOf", vec!(("object", Type::Undefined)), Type::Undefined),
+            method("isFrozen", vec!(("object", Type::Undefined)), Type::Boolean),
+            method("keys", vec!(("object", Type::Undefined)), string_array),
+            method("setPrototypeOf", vec!(("object", Type::Undefined), ("prototype", Type::Undefined)), Type::Undefined),
+            method("values", vec!(("object", Type::Undefined)), array),
+        ))
     };
 }
+
+// literals and keywords that are written like variables but are never declared in a scope
+pub const BUILT_IN_VALUES: [&str; 5] = ["undefined", "null", "true", "false", "this"];
+
+pub fn standard_globals() -> Vec<Variable> {
+    let array = Type::Composed { outer: (*ARRAY_PROTOTYPE).clone(), inner: Box::new(Type::Undefined) };
+    let promise = instance("Promise", &*PROMISE_PROTOTYPE);
+
+    let array_constructor = constructor("Array", &*ARRAY_PROTOTYPE, vec!(("length", Type::Number)), array.clone(), vec!(
+        method("from", vec!(("iterable", Type::Undefined)), array.clone()),
+        method("isArray", vec!(("value", Type::Undefined)), Type::Boolean),
+        method("of", vec!(("element", Type::Undefined)), array),
+    ));
+
+    let string_constructor = constructor("String", &*STRING_PROTOTYPE, vec!(("value", Type::Undefined)), Type::String, vec!(
+        method("fromCharCode", vec!(("code", Type::Number)), Type::String),
+        method("fromCodePoint", vec!(("code", Type::Number)), Type::String),
+        method("raw", vec!(("template", Type::Undefined)), Type::String),
+    ));
+
+    let number_constructor = constructor("Number", &*NUMBER_PROTOTYPE, vec!(("value", Type::Undefined)), Type::Number, vec!(
+        property("EPSILON", Type::Number),
+        property("MAX_SAFE_INTEGER", Type::Number),
+        property("MAX_VALUE", Type::Number),
+        property("MIN_SAFE_INTEGER", Type::Number),
+        property("MIN_VALUE", Type::Number),
+        property("NaN", Type::Number),
+        property("NEGATIVE_INFINITY", Type::Number),
+        property("POSITIVE_INFINITY", Type::Number),
+        method("isFinite", vec!(("value", Type::Undefined)), Type::Boolean),
+        method("isInteger", vec!(("value", Type::Undefined)), Type::Boolean),
+        method("isNaN", vec!(("value", Type::Undefined)), Type::Boolean),
+        method("isSafeInteger", vec!(("value", Type::Undefined)), Type::Boolean),
+        method("parseFloat", vec!(("string", Type::String)), Type::Number),
+        method("parseInt", vec!(("string", Type::String), ("radix", Type::Number)), Type::Number),
+    ));
+
+    let boolean_constructor = constructor("Boolean", &*BOOLEAN_PROTOTYPE, vec!(("value", Type::Undefined)), Type::Boolean, vec!());
+
+    let date_constructor = constructor("Date", &*DATE_PROTOTYPE, vec!(("value", Type::Undefined)), Type::String, vec!(
+        method("now", vec!(), Type::Number),
+        method("parse", vec!(("date", Type::String)), Type::Number),
+        method("UTC", vec!(("year", Type::Number), ("month", Type::Number)), Type::Number),
+    ));
+
+    let regexp_constructor = constructor("RegExp", &*REGEXP_PROTOTYPE, vec!(("pattern", Type::Undefined), ("flags", Type::String)), Type::RegExp, vec!());
+
+    let map_constructor = constructor("Map", &*MAP_PROTOTYPE, vec!(("entries", Type::Undefined)), Type::Undefined, vec!());
+    let set_constructor = constructor("Set", &*SET_PROTOTYPE, vec!(("values", Type::Undefined)), Type::Undefined, vec!());
+
+    let promise_constructor = constructor("Promise", &*PROMISE_PROTOTYPE, vec!(("executor", Type::Undefined)), Type::Undefined, vec!(
+        method("all", vec!(("promises", Type::Undefined)), promise.clone()),
+        method("allSettled", vec!(("promises", Type::Undefined)), promise.clone()),
+        method("any", vec!(("promises", Type::Undefined)), promise.clone()),
+        method("race", vec!(("promises", Type::Undefined)), promise.clone()),
+        method("reject", vec!(("reason", Type::Undefined)), promise.clone()),
+        method("resolve", vec!(("value", Type::Undefined)), promise),
+    ));
+
+    let error_constructor = constructor("Error", &*ERROR_PROTOTYPE, vec!(("message", Type::String)), Type::Undefined, vec!());
+
+    let math = namespace("Math", vec!(
+        property("E", Type::Number),
+        property("LN10", Type::Number),
+        property("LN2", Type::Number),
+        property("LOG10E", Type::Number),
+        property("LOG2E", Type::Number),
+        property("PI", Type::Number),
+        property("SQRT1_2", Type::Number),
+        property("SQRT2", Type::Number),
+        method("abs", vec!(("x", Type::Number)), Type::Number),
+        method("acos", vec!(("x", Type::Number)), Type::Number),
+        method("asin", vec!(("x", Type::Number)), Type::Number),
+        method("atan", vec!(("x", Type::Number)), Type::Number),
+        method("atan2", vec!(("y", Type::Number), ("x", Type::Number)), Type::Number),
+        method("cbrt", vec!(("x", Type::Number)), Type::Number),
+        method("ceil", vec!(("x", Type::Number)), Type::Number),
+        method("cos", vec!(("x", Type::Number)), Type::Number),
+        method("exp", vec!(("x", Type::Number)), Type::Number),
+        method("floor", vec!(("x", Type::Number)), Type::Number),
+        method("hypot", vec!(("x", Type::Number), ("y", Type::Number)), Type::Number),
+        method("log", vec!(("x", Type::Number)), Type::Number),
+        method("log10", vec!(("x", Type::Number)), Type::Number),
+        method("log2", vec!(("x", Type::Number)), Type::Number),
+        method("max", vec!(("x", Type::Number), ("y", Type::Number)), Type::Number),
+        method("min", vec!(("x", Type::Number), ("y", Type::Number)), Type::Number),
+        method("pow", vec!(("base", Type::Number), ("exponent", Type::Number)), Type::Number),
+        method("random", vec!(), Type::Number),
+        method("round", vec!(("x", Type::Number)), Type::Number),
+        method("sign", vec!(("x", Type::Number)), Type::Number),
+        method("sin", vec!(("x", Type::Number)), Type::Number),
+        method("sqrt", vec!(("x", Type::Number)), Type::Number),
+        method("tan", vec!(("x", Type::Number)), Type::Number),
+        method("trunc", vec!(("x", Type::Number)), Type::Number),
+    ));
+
+    let json = namespace("JSON", vec!(
+        method("parse", vec!(("text", Type::String)), Type::Undefined),
+        method("stringify", vec!(("value", Type::Undefined)), Type::String),
+    ));
+
+    let console = namespace("console", vec!(
+        method("assert", vec!(("condition", Type::Undefined)), Type::Undefined),
+        method("count", vec!(("label", Type::String)), Type::Undefined),
+        method("debug", vec!(), Type::Undefined),
+        method("dir", vec!(("object", Type::Undefined)), Type::Undefined),
+        method("error", vec!(), Type::Undefined),
+        method("group", vec!(), Type::Undefined),
+        method("groupEnd", vec!(), Type::Undefined),
+        method("info", vec!(), Type::Undefined),
+        method("log", vec!(), Type::Undefined),
+        method("table", vec!(("data", Type::Undefined)), Type::Undefined),
+        method("time", vec!(("label", Type::String)), Type::Undefined),
+        method("timeEnd", vec!(("label", Type::String)), Type::Undefined),
+        method("trace", vec!(), Type::Undefined),
+        method("warn", vec!(), Type::Undefined),
+    ));
+
+    vec!(
+        global("Object", (&*OBJECT).clone()),
+        global("Array", array_constructor),
+        global("String", string_constructor),
+        global("Number", number_constructor),
+        global("Boolean", boolean_constructor),
+        global("Date", date_constructor),
+        global("RegExp", regexp_constructor),
+        global("Map", map_constructor),
+        global("Set", set_constructor),
+        global("Promise", promise_constructor),
+        global("Error", error_constructor),
+        global("Math", math),
+        global("JSON", json),
+        global("console", console),
+        global("NaN", Type::Number),
+        global("Infinity", Type::Number),
+        global("isFinite", function("isFinite", vec!(("value", Type::Undefined)), Type::Boolean)),
+        global("isNaN", function("isNaN", vec!(("value", Type::Undefined)), Type::Boolean)),
+        global("parseFloat", function("parseFloat", vec!(("string", Type::String)), Type::Number)),
+        global("parseInt", function("parseInt", vec!(("string", Type::String), ("radix", Type::Number)), Type::Number)),
+        global("encodeURIComponent", function("encodeURIComponent", vec!(("component", Type::String)), Type::String)),
+        global("decodeURIComponent", function("decodeURIComponent", vec!(("component", Type::String)), Type::String)),
+    )
+}
+
+// the bindings node provides to every CommonJS module, `exports` starts out as `module.exports`
+pub fn commonjs_globals() -> Vec<Variable> {
+    let exports = instance("exports", &*OBJECT_PROTOTYPE);
+    let module = Type::from(ObjectType::new(Some(String::from("module")), HashMap::from_iter(vec!(
+        property("exports", exports.clone()),
+    )), Some(CustomTypeObject::from(&*OBJECT_PROTOTYPE))));
+
+    vec!(
+        global("module", module),
+        Variable::new(String::from("exports"), exports, VariableKind::Let),
+        global("require", function("require", vec!(("id", Type::String)), Type::Undefined)),
+    )
+}
+
+pub fn browser_globals() -> Vec<Variable> {
+    let element = instance("Element", &*OBJECT_PROTOTYPE);
+    let promise = instance("Promise", &*PROMISE_PROTOTYPE);
+
+    let document = namespace("document", vec!(
+        property("body", element.clone()),
+        property("title", Type::String),
+        method("createElement", vec!(("tagName", Type::String)), element.clone()),
+        method("getElementById", vec!(("id", Type::String)), Type::union(vec!(element.clone(), Type::Null))),
+        method("querySelector", vec!(("selectors", Type::String)), Type::union(vec!(element, Type::Null))),
+    ));
+
+    let storage = namespace("localStorage", vec!(
+        method("clear", vec!(), Type::Undefined),
+        method("getItem", vec!(("key", Type::String)), Type::union(vec!(Type::String, Type::Null))),
+        method("removeItem", vec!(("key", Type::String)), Type::Undefined),
+        method("setItem", vec!(("key", Type::String), ("value", Type::String)), Type::Undefined),
+    ));
+
+    let mut globals = vec!(
+        global("document", document),
+        global("localStorage", storage),
+        global("fetch", function("fetch", vec!(("input", Type::String)), promise)),
+        global("alert", function("alert", vec!(("message", Type::String)), Type::Undefined)),
+        global("requestAnimationFrame", function("requestAnimationFrame", vec!(("callback", Type::Undefined)), Type::Number)),
+    );
+
+    globals.extend(timer_globals());
+    globals.push(global("window", namespace("window", globals.iter()
+        .map(|variable| property(variable.name(), variable.current_type().clone()))
+        .collect())));
+
+    globals
+}
+
+pub fn node_globals() -> Vec<Variable> {
+    let strings = Type::Composed { outer: (*ARRAY_PROTOTYPE).clone(), inner: Box::new(Type::String) };
+
+    let process = namespace("process", vec!(
+        property("argv", strings),
+        property("env", instance("ProcessEnv", &*OBJECT_PROTOTYPE)),
+        property("platform", Type::String),
+        method("cwd", vec!(), Type::String),
+        method("exit", vec!(("code", Type::Number)), Type::Undefined),
+    ));
+
+    let mut globals = vec!(
+        global("process", process),
+        global("__dirname", Type::String),
+        global("__filename", Type::String),
+    );
+
+    globals.extend(timer_globals());
+    globals
+}
+
+fn timer_globals() -> Vec<Variable> {
+    vec!(
+        global("setTimeout", function("setTimeout", vec!(("callback", Type::Undefined), ("delay", Type::Number)), Type::Number)),
+        global("clearTimeout", function("clearTimeout", vec!(("id", Type::Number)), Type::Undefined)),
+        global("setInterval", function("setInterval", vec!(("callback", Type::Undefined), ("delay", Type::Number)), Type::Number)),
+        global("clearInterval", function("clearInterval", vec!(("id", Type::Number)), Type::Undefined)),
+    )
+}
+
+fn global(name: &str, global_type: Type) -> Variable {
+    Variable::new(name.to_owned(), global_type, VariableKind::Const)
+}
+
+fn property(name: &str, property_type: Type) -> (String, Type) {
+    (name.to_owned(), property_type)
+}
+
+fn function_type(name: &str, arguments: Vec<(&str, Type)>, return_type: Type) -> FunctionType {
+    let arguments = arguments.into_iter()
+        .map(|(name, argument_type)| Variable::new(name.to_owned(), argument_type, VariableKind::Let))
+        .collect();
+
+    let mut function_type = FunctionType::new(arguments);
+
+    function_type.assign_name(name.to_owned());
+    function_type.set_return_type(return_type);
+
+    function_type
+}
+
+fn function(name: &str, arguments: Vec<(&str, Type)>, return_type: Type) -> Type {
+    Type::from(function_type(name, arguments, return_type))
+}
+
+fn method(name: &str, arguments: Vec<(&str, Type)>, return_type: Type) -> (String, Type) {
+    let mut method = function_type(name, arguments, return_type);
+
+    method.set_kind(FunctionKind::Method);
+
+    (name.to_owned(), Type::from(method))
+}
+
+fn constructor(name: &str, prototype: &MutexRef<ObjectType>, arguments: Vec<(&str, Type)>, return_type: Type, statics: Vec<(String, Type)>) -> Type {
+    let mut constructor = function_type(name, arguments, return_type);
+
+    constructor.properties = HashMap::from_iter(statics);
+    constructor.properties.insert(String::from("prototype"), Type::from(prototype));
+
+    Type::from(constructor)
+}
+
+fn prototype_object(name: &str, members: Vec<(String, Type)>) -> MutexRef<ObjectType> {
+    new_mutex_ref(ObjectType::new(
+        Some(name.to_owned()),
+        HashMap::from_iter(members),
+        Some(CustomTypeObject::from(&*OBJECT_PROTOTYPE))
+    ))
+}
+
+fn namespace(name: &str, members: Vec<(String, Type)>) -> Type {
+    Type::from(prototype_object(name, members))
+}
+
+fn instance(name: &str, prototype: &MutexRef<ObjectType>) -> Type {
+    Type::from(ObjectType::new(Some(name.to_owned()), HashMap::new(), Some(CustomTypeObject::from(prototype))))
+}
@@ -0,0 +1,108 @@
+use std::fmt;
+
+const MAX_SUGGESTIONS: usize = 3;
+
+// names that are close to a misspelled one, the closest first. Displayed as the end of a
+// diagnostic message, nothing is shown when there are none
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct Suggestions(Vec<String>);
+
+impl Suggestions {
+    // candidates within an edit distance of a third of the name, but at least one edit
+    pub fn closest<I: IntoIterator<Item = String>>(name: &str, candidates: I) -> Self {
+        let limit = (name.chars().count() / 3).max(1);
+        let mut matches: Vec<(usize, String)> = candidates.into_iter()
+            .filter(|candidate| candidate != name)
+            .map(|candidate| (edit_distance(name, &candidate), candidate))
+            .filter(|(distance, _)| *distance <= limit)
+            .collect();
+
+        matches.sort();
+        matches.dedup();
+
+        Suggestions(matches.into_iter().take(MAX_SUGGESTIONS).map(|(_, candidate)| candidate).collect())
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.0
+    }
+
+    // the only candidate at the smallest distance, a fix can not pick between equally close ones
+    pub fn best(&self, name: &str) -> Option<&str> {
+        let first = self.0.first()?;
+
+        match self.0.get(1) {
+            Some(second) if edit_distance(name, second) == edit_distance(name, first) => None,
+            _ => Some(first),
+        }
+    }
+}
+
+impl fmt::Display for Suggestions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<String> = self.0.iter().map(|name| format!("\"{}\"", name)).collect();
+
+        match names.split_last() {
+            None => Ok(()),
+            Some((last, [])) => write!(f, ", did you mean {}?", last),
+            Some((last, rest)) => write!(f, ", did you mean {} or {}?", rest.join(", "), last),
+        }
+    }
+}
+
+// Damerau-Levenshtein distance restricted to adjacent transpositions, `lenght` is one edit
+// away from `length`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec!(vec!(0; b.len() + 1); a.len() + 1);
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + substitution);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+
+            rows[i][j] = distance;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_adjacent_transpositions_as_one_edit() {
+        assert_eq!(edit_distance("length", "length"), 0);
+        assert_eq!(edit_distance("lenght", "length"), 1);
+        assert_eq!(edit_distance("lenth", "length"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn suggests_close_names_closest_first() {
+        let candidates = vec!(String::from("length"), String::from("lengths"), String::from("push"));
+        let suggestions = Suggestions::closest("lenght", candidates);
+
+        assert_eq!(suggestions, Suggestions(vec!(String::from("length"), String::from("lengths"))));
+        assert_eq!(suggestions.best("lenght"), Some("length"));
+        assert_eq!(suggestions.to_string(), ", did you mean \"length\" or \"lengths\"?");
+    }
+}
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use dynamic_typing::Location;
+use error::{ Diagnostics, ValidationError };
+
+const IGNORE_NEXT_LINE: &str = "rusty-ignore-next-line";
+const DISABLE: &str = "rusty-disable";
+
+// the part of the file a suppression comment silences diagnostics in, as a range of offsets
+#[derive(Debug)]
+struct Suppression {
+    rules: Vec<String>,
+    start: u32,
+    end: u32,
+    location: Location,
+}
+
+impl Suppression {
+    // rules are given by code or name, a comment without rules silences every diagnostic
+    fn silences(&self, error: &ValidationError) -> Option<usize> {
+        let start = error.location().start;
+
+        if start < self.start || start > self.end {
+            return None;
+        }
+
+        if self.rules.is_empty() {
+            return Some(0);
+        }
+
+        self.rules.iter().position(|rule| rule == error.code() || rule == error.name())
+    }
+}
+
+// `// rusty-ignore-next-line UnknownProperty` silences the line after the comment,
+// `/* rusty-disable R001 */` silences the whole file
+#[derive(Debug, Default)]
+pub struct Suppressions {
+    suppressions: Vec<Suppression>,
+}
+
+impl Suppressions {
+    pub fn parse(source: &str) -> Self {
+        let mut suppressions = Suppressions::default();
+        let mut line_start = 0;
+        let lines: Vec<&str> = source.split('\n').collect();
+
+        for (index, line) in lines.iter().enumerate() {
+            let next_line_start = line_start + line.len() + 1;
+
+            if let Some(comment) = line.find("//") {
+                let text = &line[comment + 2..];
+
+                if let Some(rules) = directive(text, IGNORE_NEXT_LINE) {
+                    let next_line = lines.get(index + 1).cloned().unwrap_or("");
+
+                    suppressions.suppressions.push(Suppression {
+                        rules,
+                        start: next_line_start as u32,
+                        end: (next_line_start + next_line.len()) as u32,
+                        location: Location { start: (line_start + comment) as u32, end: (line_start + line.len()) as u32, line: 0, column: 0 },
+                    });
+                }
+            }
+
+            line_start = next_line_start;
+        }
+
+        let mut rest = source;
+        let mut offset = 0;
+
+        while let Some(start) = rest.find("/*") {
+            let end = match rest[start..].find("*/") {
+                Some(end) => start + end,
+                None => break,
+            };
+
+            if let Some(rules) = directive(&rest[start + 2..end], DISABLE) {
+                suppressions.suppressions.push(Suppression {
+                    rules,
+                    start: 0,
+                    end: source.len() as u32,
+                    location: Location { start: (offset + start) as u32, end: (offset + end + 2) as u32, line: 0, column: 0 },
+                });
+            }
+
+            offset += end + 2;
+            rest = &rest[end + 2..];
+        }
+
+        suppressions
+    }
+
+    // removes the silenced diagnostics, every rule of a comment that silenced nothing is
+    // returned as an unused suppression
+    pub fn apply(&self, diagnostics: &mut Diagnostics) -> Vec<ValidationError> {
+        let mut used: Vec<Vec<bool>> = self.suppressions.iter()
+            .map(|suppression| vec!(false; suppression.rules.len().max(1)))
+            .collect();
+
+        let silenced: Vec<Arc<ValidationError>> = diagnostics.keys()
+            .filter(|error| {
+                let mut is_silenced = false;
+
+                for (suppression, used) in self.suppressions.iter().zip(used.iter_mut()) {
+                    if let Some(rule) = suppression.silences(error) {
+                        used[rule] = true;
+                        is_silenced = true;
+                    }
+                }
+
+                is_silenced
+            })
+            .cloned()
+            .collect();
+
+        for error in silenced {
+            diagnostics.remove(&error);
+        }
+
+        self.suppressions.iter().zip(used).flat_map(|(suppression, used)| {
+            let rules = if suppression.rules.is_empty() { vec!(String::from("all")) } else { suppression.rules.clone() };
+
+            rules.into_iter().zip(used)
+                .filter(|(_, used)| !used)
+                .map(|(rule, _)| ValidationError::UnusedSuppression { rule, location: suppression.location.clone() })
+                .collect::<Vec<_>>()
+        }).collect()
+    }
+}
+
+// the rules of a comment starting with the directive, separated by spaces or commas
+fn directive(comment: &str, name: &str) -> Option<Vec<String>> {
+    let comment = comment.trim();
+
+    if !comment.starts_with(name) {
+        return None;
+    }
+
+    let rest = &comment[name.len()..];
+
+    // `rusty-disable` is a prefix of other words
+    if !(rest.is_empty() || rest.starts_with(char::is_whitespace)) {
+        return None;
+    }
+
+    Some(rest.split(|character: char| character.is_whitespace() || character == ',')
+        .filter(|rule| !rule.is_empty())
+        .map(String::from)
+        .collect())
+}
@@ -1,24 +1,24 @@
 use ratel::ast as Ast;
 use dynamic_typing::{
-    TracedTypeChange, Location, TracedTypeMuation, Scoped, ScopeRef, SafeBorrow, Type, TracedChange
+    TracedTypeChange, Location, TracedTypeMuation, Scoped, ScopeRef, SafeBorrow, Type, TracedChange, WideningPolicy
 };
 use failure::*;
 use expressions::{ determine_expression_type, expression_to_string };
 use error::TypeError;
 
-pub fn tracing_pass<'a>(ast: Ast::StatementList, scope: ScopeRef) -> (ScopeRef, Vec<Error>) {
+pub fn tracing_pass<'a>(ast: Ast::StatementList, scope: ScopeRef, policy: WideningPolicy) -> (ScopeRef, Vec<Error>) {
     let mut error_collection = vec!();
 
     for statement in ast {
         if let Ast::Statement::Expression(expression) = statement.item {
-            trace_expression(expression, scope.clone(), &mut error_collection);
+            trace_expression(expression, scope.clone(), policy, &mut error_collection);
         }
     }
 
     (scope, error_collection)
 }
 
-fn trace_expression(expression: Ast::ExpressionNode, scope: ScopeRef, error_collection: &mut Vec<Error>) {
+fn trace_expression(expression: Ast::ExpressionNode, scope: ScopeRef, policy: WideningPolicy, error_collection: &mut Vec<Error>) {
 
     match expression.item {
         Ast::Expression::Binary(binary_expression) => {
@@ -50,6 +50,7 @@ fn trace_expression(expression: Ast::ExpressionNode, scope: ScopeRef, error_coll
                 };
 
                 variable.borrow_mut_safe(|variable| {
+                    variable.widen(&assigned_type, policy);
                     variable.change(TracedTypeChange, assigned_type.clone(), Location::from(*expression));
                 });
             }
@@ -1,5 +1,7 @@
 use ast_nodes::{ExpressionNode, ExpressionNodeStruct, NewExpressionNodeFromAst, StringNodeStruct};
+use classes::class_expression_from;
 use dynamic_typing::Location;
+use functions::function_expression_from;
 use ratel::ast as Ast;
 
 #[derive(Clone, Debug)]
@@ -22,6 +24,13 @@ pub enum AstEvent<'ast, En: ExpressionNode<'ast>> {
         right: En,
     },
 
+    LooseEquality {
+        node: En,
+        left: En,
+        right: En,
+        operator: Ast::OperatorKind,
+    },
+
     Conditional {
         node: En,
         test: En,
@@ -59,6 +68,12 @@ pub enum AstEvent<'ast, En: ExpressionNode<'ast>> {
         identifier: Ast::Identifier<'ast>,
     },
 
+    Declaration {
+        name: Ast::Identifier<'ast>,
+        init: En,
+        location: Location,
+    },
+
     Literal {
         node: En,
         literal: Ast::Literal<'ast>,
@@ -95,6 +110,12 @@ pub enum AstEvent<'ast, En: ExpressionNode<'ast>> {
         arguments: Vec<En>,
     },
 
+    New {
+        node: En,
+        constructor: En,
+        arguments: Vec<En>,
+    },
+
     Spread {
         node: En,
         argument: En,
@@ -109,6 +130,8 @@ pub enum AstEvent<'ast, En: ExpressionNode<'ast>> {
         node: En,
         params: toolshed::list::List<'ast, ratel::ast::Node<'ast, ratel::ast::Pattern<'ast>>>,
         body: AstFunctionBody<'ast>,
+        // the callee and argument position, when the function is passed directly to a call
+        callback: Option<(En, usize)>,
     },
 
     Class {
@@ -119,16 +142,16 @@ pub enum AstEvent<'ast, En: ExpressionNode<'ast>> {
 
 #[derive(Clone, Debug)]
 pub enum AstFunctionBody<'ast> {
-    StatementBlock(Ast::Block<'ast, Ast::Statement<'ast>>),
-    SingleExpression(Ast::Expression<'ast>),
+    StatementBlock(Ast::BlockNode<'ast, Ast::Statement<'ast>>),
+    SingleExpression(Ast::ExpressionNode<'ast>),
 }
 
 impl<'ast> From<Ast::expression::ArrowBody<'ast>> for AstFunctionBody<'ast> {
     fn from(value: Ast::expression::ArrowBody) -> AstFunctionBody {
         match value {
-            Ast::expression::ArrowBody::Block(block) => AstFunctionBody::StatementBlock(**block),
+            Ast::expression::ArrowBody::Block(block) => AstFunctionBody::StatementBlock(block),
             Ast::expression::ArrowBody::Expression(expression) => {
-                AstFunctionBody::SingleExpression(**expression)
+                AstFunctionBody::SingleExpression(expression)
             }
         }
     }
@@ -186,6 +209,77 @@ pub fn travel_ast_statement<'ast>(
             });
         }
 
+        Ast::Statement::Declaration(declaration) => {
+            for declarator in declaration.declarators.iter() {
+                if let Some(init) = declarator.init {
+                    let (init_node, local_event_record) = travel_expression(init);
+
+                    event_record.extend(local_event_record);
+
+                    if let Ast::Pattern::Identifier(name) = declarator.id.item {
+                        let location = Location {
+                            start: declarator.id.start,
+                            end: declarator.id.end,
+                            line: 0,
+                            column: 0,
+                        };
+
+                        event_record.push(AstEvent::Declaration {
+                            name,
+                            init: init_node,
+                            location,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ast::Statement::Return(return_statement) => {
+            if let Some(value) = return_statement.value {
+                let (_, local_event_record) = travel_expression(value);
+
+                event_record.extend(local_event_record);
+            }
+        }
+
+        Ast::Statement::Function(function) => {
+            let Ast::MandatoryName(name) = function.name;
+            let location = Location {
+                start: name.start,
+                end: function.body.end,
+                line: 0,
+                column: 0,
+            };
+            let function_expression = function_expression_from(function);
+            let node =
+                ExpressionNodeStruct::new(Ast::Expression::Function(function_expression), location);
+
+            event_record.push(AstEvent::Function {
+                node,
+                params: function.params,
+                body: AstFunctionBody::StatementBlock(function.body),
+                callback: None,
+            });
+        }
+
+        Ast::Statement::Class(class) => {
+            let Ast::MandatoryName(name) = class.name;
+            let location = Location {
+                start: name.start,
+                end: class.body.end,
+                line: 0,
+                column: 0,
+            };
+            let class_expression = class_expression_from(class);
+            let node =
+                ExpressionNodeStruct::new(Ast::Expression::Class(class_expression), location);
+
+            event_record.push(AstEvent::Class {
+                node,
+                class_expression,
+            });
+        }
+
         _ => {}
     };
 
@@ -243,6 +337,15 @@ pub fn travel_expression<'ast>(
                     });
                 }
 
+                Ast::OperatorKind::Equality | Ast::OperatorKind::Inequality => {
+                    event_record.push(AstEvent::LooseEquality {
+                        node: node.clone(),
+                        left,
+                        right,
+                        operator,
+                    });
+                }
+
                 _ => (),
             };
 
@@ -374,8 +477,17 @@ pub fn travel_expression<'ast>(
             let arguments = function_call
                 .arguments
                 .iter()
-                .map(|argument| {
-                    let (expression, local_event_record) = travel_expression(*argument);
+                .enumerate()
+                .map(|(index, argument)| {
+                    let (expression, mut local_event_record) = travel_expression(*argument);
+
+                    if let Some(AstEvent::Function { node, callback, .. }) =
+                        local_event_record.last_mut()
+                    {
+                        if *node == expression {
+                            *callback = Some((function.clone(), index));
+                        }
+                    }
 
                     event_record.extend(local_event_record);
 
@@ -392,6 +504,49 @@ pub fn travel_expression<'ast>(
             node
         }
 
+        Ast::Expression::Prefix(prefix) if prefix.operator == Ast::OperatorKind::New => {
+            let node = ExpressionNodeStruct::from(expression);
+
+            // `new Foo(...)` wraps a call expression, `new Foo` has no argument list
+            let (constructor, arguments) = match prefix.operand.item {
+                Ast::Expression::Call(call) => {
+                    let (constructor, local_event_record) = travel_expression(call.callee);
+
+                    event_record.extend(local_event_record);
+
+                    let arguments = call
+                        .arguments
+                        .iter()
+                        .map(|argument| {
+                            let (expression, local_event_record) = travel_expression(*argument);
+
+                            event_record.extend(local_event_record);
+
+                            expression
+                        })
+                        .collect();
+
+                    (constructor, arguments)
+                }
+
+                _ => {
+                    let (constructor, local_event_record) = travel_expression(prefix.operand);
+
+                    event_record.extend(local_event_record);
+
+                    (constructor, vec![])
+                }
+            };
+
+            event_record.push(AstEvent::New {
+                node: node.clone(),
+                constructor,
+                arguments,
+            });
+
+            node
+        }
+
         Ast::Expression::Prefix(prefix) => {
             let node = ExpressionNodeStruct::from(expression);
             let (operand, local_event_record) = travel_expression(prefix.operand);
@@ -481,6 +636,7 @@ pub fn travel_expression<'ast>(
                 node: node.clone(),
                 params,
                 body: function_body,
+                callback: None,
             });
 
             node
@@ -489,12 +645,13 @@ pub fn travel_expression<'ast>(
         Ast::Expression::Function(function) => {
             let node = ExpressionNodeStruct::from(expression);
             let Ast::Function { params, body, .. } = function;
-            let statement_list = AstFunctionBody::StatementBlock(**body);
+            let statement_list = AstFunctionBody::StatementBlock(body);
 
             event_record.push(AstEvent::Function {
                 node: node.clone(),
                 params,
                 body: statement_list,
+                callback: None,
             });
 
             node
@@ -1,14 +1,21 @@
+use arrays::{array_call_type, callback_argument_types, element_arguments, element_type};
 use ast_nodes::{ExpressionNode, ExpressionNodeStruct, Node};
+use classes::instance_of;
 use context::Context;
-use dynamic_typing::{new_mutex_ref, CustomType, MutexRef, SafeBorrow, Scope, Scoped, Type};
+use dynamic_typing::{
+    new_mutex_ref, CustomType, Location, MutexRef, SafeBorrow, Scope, Scoped, Type,
+};
 use error::ValidationError;
 use expression_meta_data::{MetaCarry, MetaHashMap};
 use expressions::{determine_expression_type, expression_to_string};
+use functions::{annotated_arguments, declared_return_type, function_scope, return_type_of};
 use meta_data_collection::collect_meta_data;
 use ratel::ast as Ast;
+use statics::BUILT_IN_VALUES;
 use std::clone::Clone;
 use std::sync::Arc;
-use traveler::{travel_ast, travel_ast_statement, AstEvent};
+use suggestions::Suggestions;
+use traveler::{travel_ast, travel_ast_statement, travel_expression, AstEvent, AstFunctionBody};
 
 pub fn validation_pass<'ast>(ast: Ast::StatementList<'ast>, context: &mut Context<'ast>) {
     let event_record = travel_ast(ast);
@@ -24,20 +31,28 @@ pub fn validate_events<'ast>(
         let meta_data = collect_meta_data(&data, context);
 
         match data {
-            AstEvent::Identifier { identifier, .. } => {
+            AstEvent::Identifier { node, identifier } => {
                 let variable = context.scope.locate(identifier);
 
                 let error = match variable {
                     Ok(_) => None,
                     Err(error) => {
-                        let error = ValidationError::from(error);
+                        let mut candidates = context.scope.visible_names();
+
+                        candidates.extend(BUILT_IN_VALUES.iter().map(|name| name.to_string()));
+
+                        let error = ValidationError::undefined_variable(
+                            error,
+                            candidates,
+                            node.location().clone(),
+                        );
 
                         meta_data.borrow_mut_safe(|data| data.set_error(error.into()))
                     }
                 };
 
                 if let Some(error) = error {
-                    context.errors.insert(error);
+                    context.report(error);
                 }
             }
 
@@ -47,28 +62,94 @@ pub fn validate_events<'ast>(
 
                 let their_type = value_meta_data.borrow_safe(|data| data.expression_type());
                 let own_type = receiver_meta_data.borrow_safe(|data| data.expression_type());
+                let declared_type = declared_type_of(&left, &context.scope);
 
                 meta_data.borrow_mut_safe(|data| {
                     data.adopt_errors(&value_meta_data);
                     data.adopt_errors(&receiver_meta_data);
                 });
 
-                if own_type == their_type || own_type == Type::Null {
+                // annotated variables are checked against their annotation, everything else
+                // against the type that was inferred for it
+                let validation_error = match declared_type {
+                    Some(declared_type) => {
+                        if declared_type.is_assignable_from(&their_type) {
+                            continue;
+                        }
+
+                        ValidationError::DeclaredTypeMissmatch {
+                            variable: left.source(),
+                            declared_type: declared_type.describe(),
+                            inferred_type: their_type.describe(),
+                            location: left.location().clone(),
+                        }
+                    }
+
+                    // a CommonJS module may export any value, not just the object it started with
+                    None if left.source() == "module.exports" => continue,
+
+                    None => {
+                        if own_type.is_assignable_from(&their_type) {
+                            continue;
+                        }
+
+                        ValidationError::AssignTypeMissmatch {
+                            target: left.source(),
+                            own_type: own_type.describe(),
+                            their_type: their_type.describe(),
+                            location: left.location().clone(),
+                        }
+                    }
+                };
+
+                let validation_error =
+                    meta_data.borrow_mut_safe(|data| data.set_error(validation_error.into()));
+
+                if let Some(validation_error) = validation_error {
+                    context.report(validation_error);
+                }
+            }
+
+            AstEvent::Declaration {
+                name,
+                init,
+                location,
+            } => {
+                let declared_type = match context.scope.annotations() {
+                    Some(annotations) => match annotations.at(location.start) {
+                        Some(comment) => comment.type_expression.clone().and_then(|expression| {
+                            annotations.resolve_type(&expression, &context.scope)
+                        }),
+                        None => None,
+                    },
+                    None => None,
+                };
+
+                let declared_type = match declared_type {
+                    Some(declared_type) => declared_type,
+                    None => continue,
+                };
+
+                let inferred_type = context
+                    .node_meta_data(&init)
+                    .borrow_safe(|data| data.expression_type());
+
+                if declared_type.is_assignable_from(&inferred_type) {
                     continue;
                 }
 
-                let validation_error = ValidationError::AssignTypeMissmatch {
-                    target: left.source(),
-                    own_type: own_type.to_string(),
-                    their_type: their_type.to_string(),
-                    location: left.location().clone(),
+                let validation_error = ValidationError::DeclaredTypeMissmatch {
+                    variable: name.to_string(),
+                    declared_type: declared_type.describe(),
+                    inferred_type: inferred_type.describe(),
+                    location,
                 };
 
                 let validation_error =
                     meta_data.borrow_mut_safe(|data| data.set_error(validation_error.into()));
 
                 if let Some(validation_error) = validation_error {
-                    context.errors.insert(validation_error);
+                    context.report(validation_error);
                 }
             }
 
@@ -86,9 +167,15 @@ pub fn validate_events<'ast>(
 
                 let node_location = property.location().clone();
 
+                let suggestions = Suggestions::closest(
+                    &property.source(),
+                    object_type.property_names(property.location()),
+                );
+
                 let validation_error = ValidationError::UnknownProperty {
                     object: expression_to_string(&object.expression),
                     property: property.source(),
+                    suggestions,
                     location: node_location,
                 };
 
@@ -96,7 +183,7 @@ pub fn validate_events<'ast>(
                     meta_data.borrow_mut_safe(|data| data.set_error(validation_error.into()));
 
                 if let Some(validation_error) = validation_error {
-                    context.errors.insert(validation_error);
+                    context.report(validation_error);
                 }
             }
 
@@ -138,8 +225,8 @@ pub fn validate_events<'ast>(
 
                 if left_type != right_type {
                     let validation_error = ValidationError::CompareTypeMissmatch {
-                        left_type: left_type.to_string(),
-                        right_type: right_type.to_string(),
+                        left_type: left_type.describe(),
+                        right_type: right_type.describe(),
                         location: node.location().to_owned(),
                     };
 
@@ -147,7 +234,7 @@ pub fn validate_events<'ast>(
                         meta_data.borrow_mut_safe(|data| data.set_error(validation_error.into()));
 
                     if let Some(validation_error) = validation_error {
-                        context.errors.insert(validation_error);
+                        context.report(validation_error);
                     }
                 }
 
@@ -157,6 +244,32 @@ pub fn validate_events<'ast>(
                 });
             }
 
+            // `==` and `!=` are reported between their operands, where the operator is
+            AstEvent::LooseEquality {
+                left,
+                right,
+                operator,
+                ..
+            } => {
+                let operator = match operator {
+                    Ast::OperatorKind::Inequality => "!=",
+                    _ => "==",
+                };
+
+                let validation_error = ValidationError::LooseEquality {
+                    operator: operator.to_owned(),
+                    location: Location {
+                        start: left.location().end,
+                        end: right.location().start,
+                        line: 0,
+                        column: 0,
+                    },
+                };
+
+                // not attached to the node, an if test drops the errors of the comparison in it
+                context.report(Arc::new(validation_error));
+            }
+
             AstEvent::Conditional { .. } => {}
             AstEvent::AlternateBody { .. } => {}
             AstEvent::AfterIf { .. } => {}
@@ -180,6 +293,7 @@ pub fn validate_events<'ast>(
                         {
                             let local_error = ValidationError::NonsensicalComparison {
                                 expression: test.source(),
+                                outcome: comparison_outcome(test.expression()),
                                 location: test.location().clone(),
                             };
 
@@ -189,7 +303,7 @@ pub fn validate_events<'ast>(
                 });
 
                 if let Some(x_error) = error {
-                    context.errors.insert(x_error);
+                    context.report(x_error);
                 }
 
                 block_scope.set_name("IfConsequentBlockScope".to_string());
@@ -207,12 +321,29 @@ pub fn validate_events<'ast>(
                 context.set_node_meta_data(&node, new_mutex_ref(meta_data));
             }
 
-            AstEvent::DynamicPropertyAccess { node, property, .. } => {
+            AstEvent::DynamicPropertyAccess {
+                node,
+                object,
+                property,
+            } => {
+                let object_type = context
+                    .node_meta_data(&object)
+                    .borrow_safe(|data| data.expression_type());
                 let property_type = context
                     .node_meta_data(&property)
                     .borrow_safe(|data| data.expression_type());
                 let mut meta_data = MetaCarry::new();
 
+                // numeric indices into an array yield its elements
+                if let (Some(element_type), Type::Number) =
+                    (element_type(&object_type), &property_type)
+                {
+                    meta_data.set_expression_type(element_type);
+                    context.set_node_meta_data(&node, new_mutex_ref(meta_data));
+
+                    continue;
+                }
+
                 if property_type != Type::String {
                     let error = ValidationError::InvalidType {
                         expression: property.source(),
@@ -224,7 +355,7 @@ pub fn validate_events<'ast>(
                     let error = meta_data.set_error(Arc::from(error));
 
                     if let Some(error) = error {
-                        context.errors.insert(error);
+                        context.report(error);
                     }
                 }
 
@@ -267,25 +398,47 @@ pub fn validate_events<'ast>(
                 arguments,
             } => {
                 let mut meta_data = MetaCarry::new();
-                let function_definition = context
-                    .scope
-                    .locate(&expression_to_string(&function.expression))
-                    .expect("function should exist");
-                let arguments = map_argument_types(&arguments, &context.data_map);
-
-                let return_type = function_definition.borrow_safe(|definition| {
-                    let def_type = definition.current_type();
-
-                    match def_type {
-                        Type::Function(data) => {
-                            data.borrow_safe(|data| data.return_type(&arguments))
-                        }
-                        _ => panic!("only functions can be called!"),
-                    }
-                });
+                let argument_types = map_argument_types(&arguments, &context.data_map);
+                let return_type =
+                    call_return_type(&node, &function, &argument_types, &context.scope);
 
                 meta_data.set_expression_type(return_type);
+
                 context.set_node_meta_data(&node, new_mutex_ref(meta_data));
+
+                validate_arguments(&function, &arguments, context);
+            }
+
+            AstEvent::New {
+                node,
+                constructor,
+                arguments,
+            } => {
+                // undefined constructors are already reported through their identifier
+                let constructor_type =
+                    match determine_expression_type(&constructor.expression, &context.scope) {
+                        Ok(constructor_type) => constructor_type,
+                        Err(_) => continue,
+                    };
+
+                if instance_of(&constructor_type).is_ok() {
+                    validate_arguments(&constructor, &arguments, context);
+
+                    continue;
+                }
+
+                let validation_error = ValidationError::NotConstructor {
+                    expression: constructor.source(),
+                    type_name: constructor_type.to_string(),
+                    location: node.location().clone(),
+                };
+
+                let validation_error =
+                    meta_data.borrow_mut_safe(|data| data.set_error(validation_error.into()));
+
+                if let Some(validation_error) = validation_error {
+                    context.report(validation_error);
+                }
             }
 
             AstEvent::Spread { node, argument } => {
@@ -300,17 +453,285 @@ pub fn validate_events<'ast>(
                 context.set_node_meta_data(&node, meta_data);
             }
 
-            AstEvent::Object { node, .. } => {
+            AstEvent::Object { node, expression } => {
                 let mut meta_data = MetaCarry::new();
                 let expression_type = determine_expression_type(node.expression(), &context.scope)
                     .expect("it should be possible to determine an object type");
 
-                meta_data.set_expression_type(expression_type);
+                meta_data.set_expression_type(expression_type.clone());
                 context.set_node_meta_data(&node, new_mutex_ref(meta_data));
+
+                // methods of an object literal are called with the object as their this
+                for property in expression.body.iter() {
+                    if let Ast::Property::Method { key, value } = property.item {
+                        let Ast::Function { params, body, .. } = value.item;
+
+                        validate_function_body(
+                            &params,
+                            &AstFunctionBody::StatementBlock(body),
+                            key.start,
+                            Some(expression_type.clone()),
+                            &[],
+                            context,
+                        );
+                    }
+                }
             }
-            AstEvent::Function { .. } => {}
-            AstEvent::Class { .. } => {}
+            AstEvent::Function {
+                node,
+                params,
+                body,
+                callback,
+            } => {
+                // arrow functions keep the this binding of their surrounding scope
+                let this_type = match node.expression() {
+                    Ast::Expression::Arrow(_) => None,
+                    _ => Some(Type::Undefined),
+                };
+                let argument_types = callback_types(&callback, &context.scope);
+
+                validate_function_body(
+                    &params,
+                    &body,
+                    node.location().start,
+                    this_type,
+                    &argument_types,
+                    context,
+                );
+            }
+
+            AstEvent::Class {
+                class_expression, ..
+            } => {
+                let constructor_type = meta_data.borrow_safe(|data| data.expression_type());
+                let instance_type = instance_of(&constructor_type).unwrap_or(Type::Undefined);
+
+                for member in class_expression.body.body.iter() {
+                    if let Ast::ClassMember::Method {
+                        is_static, value, ..
+                    } = member.item
+                    {
+                        let Ast::Function { params, body, .. } = value.item;
+                        let this_type = if is_static {
+                            constructor_type.clone()
+                        } else {
+                            instance_type.clone()
+                        };
+
+                        validate_function_body(
+                            &params,
+                            &AstFunctionBody::StatementBlock(body),
+                            member.start,
+                            Some(this_type),
+                            &[],
+                            context,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn validate_function_body<'ast>(
+    params: &Ast::PatternList<'ast>,
+    body: &AstFunctionBody<'ast>,
+    start: u32,
+    this_type: Option<Type>,
+    argument_types: &[Type],
+    context: &mut Context<'ast>,
+) {
+    let end = match body {
+        AstFunctionBody::StatementBlock(block) => block.end,
+        AstFunctionBody::SingleExpression(expression) => expression.end,
+    };
+    let arguments = annotated_arguments(params, argument_types, start, &context.scope);
+    let function_scope = function_scope(&arguments, this_type, &context.scope);
+    let mut function_context = context.derive(&function_scope);
+
+    let inferred_return_type = match body {
+        AstFunctionBody::StatementBlock(block) => {
+            validation_pass(block.body, &mut function_context);
+
+            return_type_of(block, &function_scope)
+        }
+
+        AstFunctionBody::SingleExpression(expression) => {
+            let (_, event_record) = travel_expression(*expression);
+
+            validate_events(event_record, &mut function_context);
+
+            determine_expression_type(expression, &function_scope).unwrap_or(Type::Undefined)
+        }
+    };
+
+    context.join(function_context);
+
+    let declared_return_type = match declared_return_type(start, &context.scope) {
+        Some(declared_return_type) => declared_return_type,
+        None => return,
+    };
+
+    if declared_return_type.is_assignable_from(&inferred_return_type) {
+        return;
+    }
+
+    context.report(Arc::new(ValidationError::ReturnTypeMissmatch {
+        declared_type: declared_return_type.describe(),
+        inferred_type: inferred_return_type.describe(),
+        location: Location {
+            start,
+            end,
+            line: 0,
+            column: 0,
+        },
+    }));
+}
+
+pub fn declared_type_of<'ast>(
+    expression: &ExpressionNodeStruct<'ast>,
+    scope: &MutexRef<Scope>,
+) -> Option<Type> {
+    match expression.expression() {
+        Ast::Expression::Identifier(name) => match scope.locate(name) {
+            Ok(variable) => variable.borrow_safe(|variable| variable.declared_type().cloned()),
+            Err(_) => None,
+        },
+        _ => None,
+    }
+}
+
+// the value a comparison of two literals always has, None if it depends on anything else
+pub fn comparison_outcome(expression: &Ast::Expression) -> Option<bool> {
+    let binary_expression = match expression {
+        Ast::Expression::Binary(binary_expression) => binary_expression,
+        _ => return None,
+    };
+
+    let is_equal = match (binary_expression.left.item, binary_expression.right.item) {
+        (Ast::Expression::Literal(left), Ast::Expression::Literal(right)) => {
+            literals_equal(&left, &right)?
+        }
+        _ => return None,
+    };
+
+    match binary_expression.operator {
+        Ast::OperatorKind::StrictEquality | Ast::OperatorKind::Equality => Some(is_equal),
+        Ast::OperatorKind::StrictInequality | Ast::OperatorKind::Inequality => Some(!is_equal),
+        _ => None,
+    }
+}
+
+// only literals of the same kind are compared, loose comparisons convert everything else
+fn literals_equal(left: &Ast::Literal, right: &Ast::Literal) -> Option<bool> {
+    match (left, right) {
+        (Ast::Literal::Number(left), Ast::Literal::Number(right)) => {
+            Some(left.parse::<f64>().ok()? == right.parse::<f64>().ok()?)
+        }
+
+        // escapes could spell the same string differently
+        (Ast::Literal::String(left), Ast::Literal::String(right)) => {
+            if left.contains('\\') || right.contains('\\') || left.len() < 2 || right.len() < 2 {
+                return None;
+            }
+
+            Some(left[1..left.len() - 1] == right[1..right.len() - 1])
+        }
+
+        (Ast::Literal::True, Ast::Literal::True)
+        | (Ast::Literal::False, Ast::Literal::False)
+        | (Ast::Literal::Null, Ast::Literal::Null)
+        | (Ast::Literal::Undefined, Ast::Literal::Undefined) => Some(true),
+
+        (Ast::Literal::True, Ast::Literal::False) | (Ast::Literal::False, Ast::Literal::True) => {
+            Some(false)
+        }
+
+        _ => None,
+    }
+}
+
+pub fn call_return_type<'ast>(
+    node: &ExpressionNodeStruct<'ast>,
+    function: &ExpressionNodeStruct<'ast>,
+    arguments: &Vec<Type>,
+    scope: &MutexRef<Scope>,
+) -> Type {
+    if let Ast::Expression::Call(call_expression) = node.expression() {
+        if let Some(return_type) = array_call_type(call_expression, scope) {
+            return return_type;
+        }
+    }
+
+    // unknown callees are reported by the identifier and property access checks
+    match determine_expression_type(&function.expression, scope) {
+        Ok(Type::Function(data)) => data.borrow_safe(|data| data.return_type(arguments)),
+        _ => Type::Undefined,
+    }
+}
+
+pub fn validate_arguments<'ast>(
+    function: &ExpressionNodeStruct<'ast>,
+    arguments: &Vec<ExpressionNodeStruct<'ast>>,
+    context: &mut Context<'ast>,
+) {
+    let argument_types = map_argument_types(arguments, &context.data_map);
+    let parameter_types = parameter_types(function, arguments.len(), &context.scope);
+    let checked_arguments = arguments
+        .iter()
+        .zip(argument_types.iter())
+        .zip(parameter_types.iter());
+
+    for ((argument, argument_type), parameter_type) in checked_arguments {
+        if parameter_type.is_assignable_from(argument_type) {
+            continue;
+        }
+
+        let validation_error = ValidationError::ArgumentTypeMissmatch {
+            function: function.source(),
+            argument: argument.source(),
+            expected_type: parameter_type.describe(),
+            their_type: argument_type.describe(),
+            location: argument.location().clone(),
+        };
+
+        // every argument is reported, the error of the call node only holds one
+        context.report(Arc::new(validation_error));
+    }
+}
+
+// the types the arguments of a call have to be assignable to, unknown parameters accept anything
+pub fn parameter_types<'ast>(
+    function: &ExpressionNodeStruct<'ast>,
+    argument_count: usize,
+    scope: &MutexRef<Scope>,
+) -> Vec<Type> {
+    // every argument of push and unshift becomes an element of the array
+    if let Some(element_type) = element_arguments(&function.expression, scope) {
+        return vec![element_type; argument_count];
+    }
+
+    match determine_expression_type(&function.expression, scope) {
+        Ok(Type::Function(data)) => data.borrow_safe(|data| {
+            data.arguments()
+                .iter()
+                .map(|argument| argument.current_type().clone())
+                .collect()
+        }),
+        _ => vec![],
+    }
+}
+
+// the types a callee passes into a function literal it receives as an argument
+pub fn callback_types<'ast>(
+    callback: &Option<(ExpressionNodeStruct<'ast>, usize)>,
+    scope: &MutexRef<Scope>,
+) -> Vec<Type> {
+    match callback {
+        Some((function, index)) => {
+            callback_argument_types(&function.expression, *index, scope).unwrap_or(vec![])
         }
+        None => vec![],
     }
 }
 